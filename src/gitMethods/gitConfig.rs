@@ -0,0 +1,67 @@
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::config::{Config,ConfigScope,readConfigFile,writeConfigFile,normalizeKey};
+
+use std::path::{Path,PathBuf};
+
+//gitc config的操作
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum ConfigAction
+{
+    Get(String),
+    Set(String,String),
+    Unset(String),
+    List,
+}
+
+//scope对应的配置文件，仓库配置要求在仓库中运行
+fn scopePath(scope:ConfigScope,root:Option<&Path>)->Result<PathBuf,GitcError>
+{
+    if scope==ConfigScope::Repository && root.is_none()
+    {
+        return Err(GitcError::NotARepository);
+    }
+    scope.path(root).ok_or_else(|| GitcError::InvalidConfig(String::from("no user-level config file (set HOME or GITC_CONFIG_GLOBAL)")))
+}
+
+//scope为None时get和list读取所有层合并后的配置，set和unset修改./.gitc/config；
+//给出scope时只读写这一个文件。root为工作区的根目录，不在仓库中时为None
+pub fn gitConfig(action:&ConfigAction,scope:Option<ConfigScope>,root:Option<&Path>)->Result<(),GitcError>
+{
+    let entries=||->Result<Vec<(String,String)>,GitcError>
+    {
+        match scope
+        {
+            Some(scope)=>readConfigFile(&scopePath(scope,root)?),
+            None=>Ok(Config::load(root)?.list()),
+        }
+    };
+    match action
+    {
+        ConfigAction::Get(key)=>
+        {
+            let normalized=normalizeKey(key)?;
+            let entries=entries()?;
+            let (_,value)=entries.iter().rev().find(|(found,_)| *found==normalized).ok_or_else(|| GitcError::UnknownConfigKey(key.clone()))?;
+            println!("{}",value);
+        }
+        ConfigAction::List=>
+        {
+            for (key,value) in entries()?
+            {
+                println!("{}={}",key,value);
+            }
+        }
+        ConfigAction::Set(key,value)=>
+        {
+            writeConfigFile(&scopePath(scope.unwrap_or(ConfigScope::Repository),root)?,key,Some(value))?;
+        }
+        ConfigAction::Unset(key)=>
+        {
+            if !writeConfigFile(&scopePath(scope.unwrap_or(ConfigScope::Repository),root)?,key,None)?
+            {
+                return Err(GitcError::UnknownConfigKey(key.clone()));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use crate::gitUtils::gitUtils::HEAD;
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitUtils::Objects;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::config::Config;
+use crate::gitUtils::lineDiff::{DiffAlgorithm,unifiedHunks,isBinary};
+use crate::gitMethods::gitMethods::commitBlobs;
+use crate::gitMethods::gitRevParse::revParse;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+//比较的两端
+pub enum DiffMode
+{
+    //工作区相对暂存区
+    WorkingTree,
+    //暂存区相对当前分支的commit
+    Cached,
+    //两个commit的快照
+    Commits(String,String),
+}
+
+pub struct DiffOptions
+{
+    pub context:usize,
+    pub algorithm:DiffAlgorithm,
+}
+
+impl Default for DiffOptions {
+    fn default()->Self
+    {
+        DiffOptions
+        {
+            context:3,
+            algorithm:DiffAlgorithm::Myers,
+        }
+    }
+}
+
+impl DiffOptions {
+    //按diff.context和diff.algorithm设置的默认值，命令行的-U和--diff-algorithm会再覆盖它们
+    pub fn fromConfig(config:&Config)->Result<DiffOptions,GitcError>
+    {
+        let mut options=DiffOptions::default();
+        if let Some(context)=config.getInt("diff.context")?
+        {
+            options.context=usize::try_from(context).map_err(|_| GitcError::InvalidConfig(format!("bad diff.context {}",context)))?;
+        }
+        if let Some(name)=config.get("diff.algorithm")
+        {
+            options.algorithm=DiffAlgorithm::fromName(&name).ok_or_else(|| GitcError::InvalidConfig(format!("unknown diff algorithm '{}'",name)))?;
+        }
+        Ok(options)
+    }
+}
+
+//"./demo/demo1.txt"在diff头中显示为"demo/demo1.txt"
+fn displayPath(path:&str)->&str
+{
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+//生成两组文件(路径到内容)之间的diff，只出现在一边的文件作为新增或删除
+pub fn diffFiles(old:&BTreeMap<String,Vec<u8>>,new:&BTreeMap<String,Vec<u8>>,options:&DiffOptions)->String
+{
+    let mut out=String::new();
+    let mut paths:Vec<&String>=old.keys().chain(new.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    for path in paths
+    {
+        let before=old.get(path);
+        let after=new.get(path);
+        if before==after
+        {
+            continue;
+        }
+        let name=displayPath(path);
+        out.push_str(&format!("diff --git a/{} b/{}\n",name,name));
+        match (before,after)
+        {
+            (None,_)=>out.push_str("new file mode 100644\n"),
+            (_,None)=>out.push_str("deleted file mode 100644\n"),
+            _=>{}
+        }
+        let oldLabel=before.map_or(String::from("/dev/null"),|_| format!("a/{}",name));
+        let newLabel=after.map_or(String::from("/dev/null"),|_| format!("b/{}",name));
+        let before=before.map_or(&[][..],|contents| contents.as_slice());
+        let after=after.map_or(&[][..],|contents| contents.as_slice());
+        if isBinary(before) || isBinary(after)
+        {
+            out.push_str(&format!("Binary files {} and {} differ\n",oldLabel,newLabel));
+            continue;
+        }
+        out.push_str(&format!("--- {}\n+++ {}\n",oldLabel,newLabel));
+        out.push_str(&unifiedHunks(before,after,options.context,options.algorithm));
+    }
+    out
+}
+
+//把路径到blob SHA1和mode的map换成路径到内容
+fn contentsOf(objects:&Objects,files:BTreeMap<String,(String,u32)>)->Result<BTreeMap<String,Vec<u8>>,GitcError>
+{
+    let mut res=BTreeMap::new();
+    for (path,(id,_)) in files
+    {
+        res.insert(path,objects.blobContents(&id)?);
+    }
+    Ok(res)
+}
+
+fn commitFiles(repo:&Repository,name:&str)->Result<BTreeMap<String,Vec<u8>>,GitcError>
+{
+    let commit=revParse(repo,name)?;
+    contentsOf(&repo.objects,commitBlobs(&repo.objects,Some(&commit))?)
+}
+
+//暂存区中的文件
+fn stagedFiles(head:&HEAD,objects:&Objects)->Result<BTreeMap<String,Vec<u8>>,GitcError>
+{
+    contentsOf(objects,head.index.blobs())
+}
+
+//按照mode生成diff文本
+pub fn diffText(repo:&Repository,mode:&DiffMode,options:&DiffOptions)->Result<String,GitcError>
+{
+    let Repository{root,head,objects,..}=repo;
+    let (old,new)=match mode
+    {
+        DiffMode::WorkingTree=>
+        {
+            let staged=stagedFiles(head,objects)?;
+            let mut disk=BTreeMap::new();
+            for path in staged.keys()
+            {
+                let file=root.join(path);
+                if file.is_file()
+                {
+                    disk.insert(path.clone(),fs::read(file)?);
+                }
+            }
+            (staged,disk)
+        }
+        DiffMode::Cached=>
+        {
+            let committed=contentsOf(objects,commitBlobs(objects,head.currentBranch()?.borrow().commitpointer.as_deref())?)?;
+            (committed,stagedFiles(head,objects)?)
+        }
+        DiffMode::Commits(from,to)=>(commitFiles(repo,from)?,commitFiles(repo,to)?),
+    };
+    Ok(diffFiles(&old,&new,options))
+}
+
+//打印diff
+pub fn gitDiff(repo:&Repository,mode:&DiffMode,options:&DiffOptions)->Result<(),GitcError>
+{
+    print!("{}",diffText(repo,mode,options)?);
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use crate::gitUtils::gitUtils::{HEAD,Objects};
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::config::Config;
+use crate::gitUtils::objectStore::objectPath;
+use crate::gitUtils::pack::{writePack,verifyPack};
+use crate::gitMethods::gitRepack::looseIds;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration,SystemTime};
+use walkdir::WalkDir;
+
+//没有被引用的对象默认保留两周再删除，和git的gc.pruneExpire相同
+pub const DEFAULT_PRUNE:Duration=Duration::from_secs(14*24*60*60);
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct GcSummary
+{
+    pub reachable:usize,
+    pub pruned:usize,
+    //没有被引用但还在保留期内的对象
+    pub kept:usize,
+}
+
+//解析"now"、"never"以及"2.weeks.ago"这样的保留期，never返回None
+pub fn parsePrune(value:&str)->Option<Option<Duration>>
+{
+    match value
+    {
+        "now"=>return Some(Some(Duration::ZERO)),
+        "never"=>return Some(None),
+        _=>{}
+    }
+    let mut parts=value.trim_end_matches(".ago").splitn(2,'.');
+    let count:u64=parts.next()?.parse().ok()?;
+    let unit=match parts.next()?.trim_end_matches('s')
+    {
+        "second"=>1,
+        "minute"=>60,
+        "hour"=>60*60,
+        "day"=>24*60*60,
+        "week"=>7*24*60*60,
+        _=>return None,
+    };
+    Some(Some(Duration::from_secs(count.checked_mul(unit)?)))
+}
+
+//命令行没有给出--prune时的保留期：gc.pruneExpire，没有设置时为两周
+pub fn pruneExpire(config:&Config)->Result<Option<Duration>,GitcError>
+{
+    match config.get("gc.pruneExpire")
+    {
+        Some(value)=>parsePrune(&value).ok_or_else(|| GitcError::InvalidConfig(format!("bad gc.pruneExpire '{}'",value))),
+        None=>Ok(Some(DEFAULT_PRUNE)),
+    }
+}
+
+//目录下所有文件的内容，目录不存在时为空
+fn filesUnder(dir:&Path)->Result<Vec<String>,GitcError>
+{
+    let mut res=vec![];
+    if !dir.is_dir()
+    {
+        return Ok(res);
+    }
+    for entry in WalkDir::new(dir).sort_by_file_name()
+    {
+        let entry=entry.map_err(|err| GitcError::Io(err.into()))?;
+        if entry.file_type().is_file()
+        {
+            res.push(fs::read_to_string(entry.path())?);
+        }
+    }
+    Ok(res)
+}
+
+fn isId(word:&str)->bool
+{
+    word.len()==40 && word.bytes().all(|byte| byte.is_ascii_hexdigit()) && word.bytes().any(|byte| byte!=b'0')
+}
+
+//gc的起点：各分支的commitpointer、暂存区、mergeHead、./.gitc/refs/tags中的tag以及./.gitc/logs中的reflog。
+//gitc自己不写tag和reflog，这两处是为了不删掉其他工具在./.gitc中写入的引用所指向的对象
+fn roots(root:&Path,head:&HEAD)->Result<Vec<String>,GitcError>
+{
+    let mut res=vec![];
+    for branch in head.branch.values()
+    {
+        res.extend(branch.borrow().commitpointer.clone());
+    }
+    res.extend(head.index.entries.values().map(|entry| entry.id.clone()));
+    res.extend(head.mergeHead.clone());
+    for tag in filesUnder(&root.join("./.gitc/refs/tags"))?
+    {
+        res.push(tag.trim().to_string());
+    }
+    //reflog的每一行为"<旧SHA1> <新SHA1> <作者> <时间>\t<说明>"
+    for log in filesUnder(&root.join("./.gitc/logs"))?
+    {
+        for line in log.lines()
+        {
+            res.extend(line.split(' ').take(2).map(String::from));
+        }
+    }
+    res.retain(|id| isId(id));
+    Ok(res)
+}
+
+//从roots出发沿着commit的tree、parent以及tree中的每一项找到的所有对象，对象从objects.root下读取
+pub fn reachableIds(objects:&Objects,head:&HEAD)->Result<BTreeSet<String>,GitcError>
+{
+    let mut reachable=BTreeSet::new();
+    let mut stack=roots(&objects.root,head)?;
+    while let Some(id)=stack.pop()
+    {
+        if !reachable.insert(id.clone())
+        {
+            continue;
+        }
+        let (kind,body)=match objects.readObject(&id)
+        {
+            Ok(object)=>object,
+            //tag和reflog中可能还记录着已经不存在的对象，它们不影响其他对象
+            Err(GitcError::ObjectNotFound(_))=>continue,
+            Err(err)=>return Err(err),
+        };
+        match kind.as_str()
+        {
+            "commit"=>
+            {
+                for line in String::from_utf8_lossy(&body).lines().take_while(|line| !line.is_empty())
+                {
+                    if let Some(child)=line.strip_prefix("tree ").or_else(|| line.strip_prefix("parent "))
+                    {
+                        stack.push(child.to_string());
+                    }
+                }
+            }
+            "tree"=>
+            {
+                let mut pos=0;
+                while let Some(nul)=body[pos..].iter().position(|byte| *byte==0)
+                {
+                    let start=pos+nul+1;
+                    let raw=body.get(start..start+20).ok_or_else(|| GitcError::CorruptStore(format!("bad tree {}",id)))?;
+                    stack.push(raw.iter().map(|byte| format!("{:02x}",byte)).collect());
+                    pos=start+20;
+                }
+            }
+            _=>{}
+        }
+    }
+    Ok(reachable)
+}
+
+fn expired(modified:SystemTime,prune:Option<Duration>)->bool
+{
+    match prune
+    {
+        Some(prune)=>modified.elapsed().unwrap_or_default()>=prune,
+        None=>false,
+    }
+}
+
+//删除从任何分支、tag、reflog和暂存区都找不到的对象，prune为保留期，None表示不删除
+pub fn gitGc(repo:&mut Repository,prune:Option<Duration>)->Result<GcSummary,GitcError>
+{
+    let Repository{root,head,objects,..}=repo;
+    let reachable=reachableIds(objects,head)?;
+    let mut summary=GcSummary{reachable:reachable.len(),..GcSummary::default()};
+
+    for id in looseIds(root)?
+    {
+        if reachable.contains(&id)
+        {
+            continue;
+        }
+        let path=objectPath(root,&id);
+        if expired(fs::metadata(&path)?.modified()?,prune)
+        {
+            fs::remove_file(&path)?;
+            if let Some(dir)=path.parent()
+            {
+                let _=fs::remove_dir(dir);
+            }
+            summary.pruned+=1;
+        }
+        else
+        {
+            summary.kept+=1;
+        }
+    }
+
+    //pack中的对象按pack文件的修改时间计算保留期，有对象要删除时把所有pack重写成一个
+    let packs=objects.packs()?;
+    let mut keep=BTreeSet::new();
+    let mut pruned=0;
+    for pack in packs.iter()
+    {
+        let old=expired(fs::metadata(&pack.path)?.modified()?,prune);
+        for entry in pack.entries.iter()
+        {
+            if reachable.contains(&entry.id)
+            {
+                keep.insert(entry.id.clone());
+            }
+            else if old
+            {
+                pruned+=1;
+            }
+            else
+            {
+                summary.kept+=1;
+                keep.insert(entry.id.clone());
+            }
+        }
+    }
+    if pruned>0
+    {
+        //通过缓存的index读出要保留的对象
+        let mut kept=vec![];
+        for id in keep.iter()
+        {
+            kept.push(objects.readObject(id)?);
+        }
+        let packPath=if kept.is_empty() {None} else {Some(writePack(root,&kept,objects.level())?.0)};
+        objects.forgetPacks();
+        //新的pack缺少要保留的对象时不删除旧的pack，否则这些对象就丢了
+        if let Some(packPath)=packPath.as_ref()
+        {
+            verifyPack(packPath,&keep)?;
+        }
+        for oldPack in packs.iter().map(|pack| &pack.path).filter(|oldPack| Some(*oldPack)!=packPath.as_ref())
+        {
+            fs::remove_file(oldPack.with_extension("idx"))?;
+            fs::remove_file(oldPack)?;
+        }
+        summary.pruned+=pruned;
+    }
+
+    objects.blobmap.retain(|id,_| reachable.contains(id));
+    objects.treemap.retain(|id,_| reachable.contains(id));
+    objects.commitmap.retain(|id,_| reachable.contains(id));
+    Ok(summary)
+}
@@ -0,0 +1,82 @@
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::ignore::{IgnoreRules,IgnoreRule};
+use crate::gitMethods::gitMethods::{fatherName,removeWorkingFile};
+use crate::gitMethods::gitStatus::workingFiles;
+
+use std::collections::BTreeSet;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct CleanOptions
+{
+    //-n：只列出会被删除的文件
+    pub dryRun:bool,
+    //-d：同时删除没有任何暂存文件的文件夹中的文件
+    pub directories:bool,
+    //-x：被忽略的文件也删除
+    pub ignored:bool,
+    //-X：只删除被忽略的文件
+    pub onlyIgnored:bool,
+}
+
+//删除工作区中没有暂存的文件，返回被删除(dryRun时为将被删除)的文件
+pub fn cleanFiles(repo:&Repository,options:&CleanOptions)->Result<Vec<String>,GitcError>
+{
+    let tracked:BTreeSet<String>=repo.head.index.entries.keys().cloned().collect();
+    let rules=IgnoreRules::load(&repo.root)?;
+    //文件夹中有暂存的文件时才算已经被跟踪的文件夹
+    let trackedDir=|dir:&str| dir=="." || tracked.iter().any(|path| path.starts_with(&format!("{}/",dir)));
+
+    let mut res=vec![];
+    for path in workingFiles(&repo.root,".")?
+    {
+        if tracked.contains(&path)
+        {
+            continue;
+        }
+        let ignored=rules.isIgnored(&path,false);
+        let selected=if options.onlyIgnored {ignored} else {options.ignored || !ignored};
+        if !selected || (!options.directories && !trackedDir(&fatherName(&path)))
+        {
+            continue;
+        }
+        if !options.dryRun
+        {
+            removeWorkingFile(&repo.root,&path)?;
+        }
+        res.push(path);
+    }
+    Ok(res)
+}
+
+pub fn gitClean(repo:&Repository,options:&CleanOptions)->Result<(),GitcError>
+{
+    let action=if options.dryRun {"Would remove"} else {"Removing"};
+    for path in cleanFiles(repo,options)?
+    {
+        println!("{} {}",action,path);
+    }
+    Ok(())
+}
+
+//每个路径匹配到的规则，没有匹配的路径为None
+pub fn checkIgnore(repo:&Repository,paths:&[String])->Result<Vec<(String,Option<IgnoreRule>)>,GitcError>
+{
+    let rules=IgnoreRules::load(&repo.root)?;
+    Ok(paths.iter().map(|path| (path.clone(),rules.matchOf(path,repo.root.join(path).is_dir()).cloned())).collect())
+}
+
+//打印被忽略的路径，verbose时打印"<规则文件>:<行号>:<规则>\t<路径>"，包括"!"规则
+pub fn gitCheckIgnore(repo:&Repository,paths:&[String],verbose:bool)->Result<(),GitcError>
+{
+    for (path,rule) in checkIgnore(repo,paths)?
+    {
+        match rule
+        {
+            Some(rule) if verbose=>println!("{}:{}:{}\t{}",rule.source,rule.line,rule.text,path),
+            Some(rule) if !rule.negated=>println!("{}",path),
+            _=>{}
+        }
+    }
+    Ok(())
+}
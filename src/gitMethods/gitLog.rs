@@ -0,0 +1,251 @@
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitUtils::Objects;
+use crate::gitUtils::gitUtils::Commit;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitMethods::gitMethods::snapshotBlobs;
+use crate::gitMethods::gitRevParse::revParse;
+use crate::gitMethods::gitMergeBase::{ancestors,Ancestry};
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+pub struct LogOptions
+{
+    //从哪个分支或commit开始，None表示当前分支
+    pub start:Option<String>,
+    pub oneline:bool,
+    pub graph:bool,
+    pub maxCount:Option<usize>,
+    pub author:Option<String>,
+    pub grep:Option<String>,
+    //只显示提交时间不早于since、不晚于until的commit，UNIX时间戳
+    pub since:Option<i64>,
+    pub until:Option<i64>,
+    //只显示改动了这个文件(或文件夹下的文件)的commit
+    pub path:Option<String>,
+}
+
+//commit的快照中path本身或path下的所有文件
+fn filesUnder(objects:&Objects,commit:&Rc<RefCell<Commit>>,path:&str)->Result<BTreeMap<String,String>,GitcError>
+{
+    let prefix=format!("{}/",path.trim_end_matches('/'));
+    Ok(snapshotBlobs(objects,&commit.borrow().snapshot)?.into_iter()
+        .filter(|(name,_)| name==path || name.starts_with(&prefix))
+        .collect())
+}
+
+//path在这个commit中的内容和每个直接父commit都不同时才算改动了path
+fn touches(objects:&Objects,commit:&Rc<RefCell<Commit>>,path:&str)->Result<bool,GitcError>
+{
+    let files=filesUnder(objects,commit,path)?;
+    let parents=commit.borrow().directParents();
+    if parents.is_empty()
+    {
+        return Ok(!files.is_empty());
+    }
+    for parent in parents
+    {
+        if filesUnder(objects,&objects.loadCommit(&parent)?,path)?==files
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+//按拓扑顺序排列start和它的祖先：子commit总在父commit之前，并尽量沿着第一父commit连续输出
+fn topoOrder(objects:&Objects,start:&str)->Result<Ancestry,GitcError>
+{
+    let all=ancestors(objects,start)?;
+    let mut children:BTreeMap<String,usize>=BTreeMap::new();
+    for (_,commit) in all.iter()
+    {
+        for parent in commit.borrow().directParents()
+        {
+            *children.entry(parent).or_insert(0)+=1;
+        }
+    }
+    let mut commits:BTreeMap<String,Rc<RefCell<Commit>>>=all.into_iter().collect();
+    let mut order=vec![];
+    let mut ready=vec![start.to_string()];
+    while let Some(id)=ready.pop()
+    {
+        let Some(commit)=commits.remove(&id) else {continue};
+        for parent in commit.borrow().directParents().into_iter().rev()
+        {
+            let count=children.entry(parent.clone()).or_insert(1);
+            *count-=1;
+            if *count==0
+            {
+                ready.push(parent);
+            }
+        }
+        order.push((id,commit));
+    }
+    Ok(order)
+}
+
+//--graph的列：每一列记录这条线上等待输出的commit
+struct Graph
+{
+    columns:Vec<String>,
+}
+
+impl Graph {
+    fn lanes(&self,commitColumn:Option<usize>)->String
+    {
+        let mut res=String::new();
+        for i in 0..self.columns.len()
+        {
+            res.push(if Some(i)==commitColumn {'*'} else {'|'});
+            res.push(' ');
+        }
+        res
+    }
+    //输出commit所在的行，并用它的父commit替换它所在的列，返回commit行以及之后的连线
+    fn next(&mut self,id:&str,parents:&[String])->(String,Vec<String>)
+    {
+        let column=match self.columns.iter().position(|column| column==id)
+        {
+            Some(column)=>column,
+            None=>
+            {
+                self.columns.push(id.to_string());
+                self.columns.len()-1
+            }
+        };
+        let line=self.lanes(Some(column));
+        let mut edges=vec![];
+        match parents.first()
+        {
+            Some(first)=>self.columns[column]=first.clone(),
+            None=>
+            {
+                self.columns.remove(column);
+                if column<self.columns.len()
+                {
+                    edges.push(self.shiftLine(column,false));
+                }
+            }
+        }
+        for parent in parents.iter().skip(1)
+        {
+            if self.columns.contains(parent)
+            {
+                continue;
+            }
+            //合并进来的分支在右边新开一列
+            let mut edge="| ".repeat(column);
+            edge.push_str("|\\");
+            for _ in column+1..self.columns.len()
+            {
+                edge.push_str(" \\");
+            }
+            edges.push(edge);
+            self.columns.insert(column+1,parent.clone());
+        }
+        //两列等待同一个commit时合并成一列
+        while let Some(duplicate)=(1..self.columns.len()).find(|&i| self.columns[..i].contains(&self.columns[i]))
+        {
+            self.columns.remove(duplicate);
+            edges.push(self.shiftLine(duplicate,true));
+        }
+        (line,edges)
+    }
+    //第column列被删掉后，它右边的列都向左移一列，merged为true时被删掉的列并入左边的列
+    fn shiftLine(&self,column:usize,merged:bool)->String
+    {
+        let mut edge="| ".repeat(column);
+        if merged
+        {
+            edge.pop();
+            edge.push('/');
+        }
+        for _ in column..self.columns.len()
+        {
+            edge.push_str(" /");
+        }
+        edge
+    }
+}
+
+//按照options生成log的每一行
+pub fn logLines(repo:&Repository,options:&LogOptions)->Result<Vec<String>,GitcError>
+{
+    let objects=&repo.objects;
+    let start=match options.start.as_ref()
+    {
+        Some(name)=>revParse(repo,name)?,
+        None=>match repo.head.currentBranch()?.borrow().commitpointer.clone()
+        {
+            Some(commit)=>commit,
+            None=>return Ok(vec![]),
+        },
+    };
+
+    let mut lines=vec![];
+    let mut graph=Graph{columns:vec![]};
+    let mut shown=0;
+    for (id,commit) in topoOrder(objects,&start)?
+    {
+        if options.maxCount.is_some_and(|max| shown>=max)
+        {
+            break;
+        }
+        let time=commit.borrow().committer.time;
+        let selected=options.author.as_ref().is_none_or(|author| commit.borrow().author.ident().contains(author.as_str()))
+            && options.since.is_none_or(|since| time>=since)
+            && options.until.is_none_or(|until| time<=until)
+            && options.grep.as_ref().is_none_or(|pattern| commit.borrow().message.contains(pattern.as_str()))
+            && match options.path.as_ref()
+            {
+                Some(path)=>touches(objects,&commit,path)?,
+                None=>true,
+            };
+        let parents=commit.borrow().directParents();
+        let (prefix,edges)=if options.graph {graph.next(&id,&parents)} else {(String::new(),vec![])};
+        if !selected
+        {
+            continue;
+        }
+        shown+=1;
+        let commit=commit.borrow();
+        if options.oneline
+        {
+            lines.push(format!("{}{} {}",prefix,&id[..7],commit.message));
+            lines.extend(edges);
+        }
+        else
+        {
+            let body=if options.graph {graph.lanes(None)} else {String::new()};
+            lines.push(format!("{}commit {}",prefix,id));
+            lines.extend(edges);
+            if parents.len()>1
+            {
+                let short:Vec<&str>=parents.iter().map(|parent| &parent[..7]).collect();
+                lines.push(format!("{}Merge: {}",body,short.join(" ")));
+            }
+            lines.push(format!("{}Author: {}",body,commit.author.ident()));
+            lines.push(format!("{}Date:   {}",body,commit.author.date()));
+            lines.push(body.trim_end().to_string());
+            for line in commit.message.lines()
+            {
+                lines.push(format!("{}    {}",body,line));
+            }
+            lines.push(body.trim_end().to_string());
+        }
+    }
+    Ok(lines)
+}
+
+//从当前分支(或options.start)的commitpointer出发，沿着parents和mergeparents打印提交历史
+pub fn gitLog(repo:&Repository,options:&LogOptions)->Result<(),GitcError>
+{
+    for line in logLines(repo,options)?
+    {
+        println!("{}",line);
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitUtils::Objects;
+use crate::gitUtils::gitUtils::Commit;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitMethods::gitRevParse::revParse;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{BTreeSet,VecDeque};
+
+//(commit的SHA1,commit)组成的序列
+pub type Ancestry=Vec<(String,Rc<RefCell<Commit>>)>;
+
+//从start出发沿着parents和mergeparents遍历，按广度优先的顺序返回start和它的所有祖先
+pub fn ancestors(objects:&Objects,start:&str)->Result<Ancestry,GitcError>
+{
+    let mut res=vec![];
+    let mut visited=BTreeSet::new();
+    let mut queue=VecDeque::from([start.to_string()]);
+    while let Some(id)=queue.pop_front()
+    {
+        if visited.insert(id.clone())
+        {
+            let commit=objects.loadCommit(&id)?;
+            queue.extend(commit.borrow().directParents());
+            res.push((id,commit));
+        }
+    }
+    Ok(res)
+}
+
+//ancestor是否是descendant本身或它的祖先
+pub fn isAncestor(objects:&Objects,ancestor:&str,descendant:&str)->Result<bool,GitcError>
+{
+    Ok(ancestors(objects,descendant)?.iter().any(|(id,_)| id==ancestor))
+}
+
+//两个commit的最佳共同祖先：同时是两者祖先、且不是其他共同祖先的祖先的commit。
+//交叉合并(criss-cross)时会有多个，按离a由近到远的顺序返回
+pub fn mergeBases(objects:&Objects,a:&str,b:&str)->Result<Vec<String>,GitcError>
+{
+    let fromB:BTreeSet<String>=ancestors(objects,b)?.into_iter().map(|(id,_)| id).collect();
+    let common:Vec<String>=ancestors(objects,a)?.into_iter().map(|(id,_)| id).filter(|id| fromB.contains(id)).collect();
+
+    let mut redundant=BTreeSet::new();
+    for id in common.iter()
+    {
+        if redundant.contains(id)
+        {
+            continue;
+        }
+        for (ancestorId,_) in ancestors(objects,id)?.into_iter().skip(1)
+        {
+            redundant.insert(ancestorId);
+        }
+    }
+    Ok(common.into_iter().filter(|id| !redundant.contains(id)).collect())
+}
+
+//gitc merge-base A B，返回最佳共同祖先的SHA1
+pub fn gitMergeBase(repo:&Repository,a:&str,b:&str)->Result<Vec<String>,GitcError>
+{
+    let a=revParse(repo,a)?;
+    let b=revParse(repo,b)?;
+    mergeBases(&repo.objects,&a,&b)
+}
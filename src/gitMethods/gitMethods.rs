@@ -1,5 +1,3 @@
-
-
 use crate::gitUtils::gitUtils::References;
 use crate::gitUtils::gitUtils::HEAD;
 use crate::gitUtils::gitUtils::Branch;
@@ -8,510 +6,1012 @@ use crate::gitUtils::gitUtils::Objects;
 use crate::gitUtils::gitUtils::tree;
 use crate::gitUtils::gitUtils::Commit;
 use crate::gitUtils::gitUtils::objecttype;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::threeWay::{merge3,ConflictStyle};
+use crate::gitMethods::gitMergeBase::{mergeBases,isAncestor};
+use crate::gitMethods::gitStatus::workingFiles;
+use crate::gitUtils::wildmatch::{wildmatch,isGlob};
+use crate::gitUtils::ignore::IgnoreRules;
+use crate::gitUtils::index::{sameMode,Index,MODE_EXECUTABLE,MODE_FILE};
+use crate::gitUtils::signature::Signature;
+use crate::gitUtils::config::Config;
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::legacy::{LegacyHEAD,LegacyObjects};
+pub use crate::gitUtils::objectStore::{getSHA1,hashObject,hashBlob,treeObject,treeId,commitObject,commitId};
+use crate::gitUtils::objectStore::OBJECTS_DIR;
 
 
-use serde::{Serialize, Deserialize};
 use bincode::{serialize, deserialize};
-use std::io::{Bytes, Read, Write};
+use std::io::Write;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::fs;
 use std::fs::File;
-use std::collections::HashMap;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap,BTreeSet};
 use std::path::Path;
-use hex_literal::hex;
-use sha1::{Sha1, Digest};
-use walkdir::WalkDir;
 
 
-const Hex:[char;16]=['0','1','2','3','4','5','6','7','8','9','a','b','c','d','e','f'];
+//./.gitc/maps/version中记录的存储格式版本，2表示对象的SHA1按照git的方式计算，
+//3表示对象存放在./.gitc/objects中而不再整体写入./.gitc/maps/objs，4表示暂存区保存在./.gitc/index中，
+//5表示commit只按SHA1查找，不再以提交信息为名记录在references中，6表示commit只记录直接的父commit，
+//7表示head中的分支只记录commit的SHA1，commit和tree也只按SHA1引用父commit、子tree和blob
+const STORE_VERSION:u32=7;
+const HEAD_FILE:&str="./.gitc/maps/head";
+const VERSION_FILE:&str="./.gitc/maps/version";
+const OBJS_FILE:&str="./.gitc/maps/objs";
 
 pub fn fatherName(path:&str)->String
 {
-    let p=Path::new(path);
-    let mut fileName=path.to_string();
-    let namearray=fileName.split("/");
-    let arraysize=namearray.clone().count();
-    let mut c:usize=1;
-    let mut pname=String::new();
+    let namearray:Vec<&str>=path.split('/').collect();
+    let arraysize=namearray.len();
     if arraysize==1
     {
-        return String::from("");
+        String::from("")
     }
     else if arraysize==2
     {
-        return String::from(".");
+        String::from(".")
     }
     else
     {
-        for s in namearray
-        {
-            if c==arraysize
-            {
-                break;
-            }
-            if c>1
-            {
-                pname.push_str("/");
-            }
-            pname.push_str(s);
-            c=c+1;
-        }
-        return pname;
+        namearray[..arraysize-1].join("/")
     }
 }
 
-pub fn gitBranch(head:&mut HEAD,branchname:String,persistence:bool)
+//还没有提交过的仓库的head：只有master分支
+fn newHead()->HEAD
 {
-    if head.branch.contains_key(&branchname)==false
+    let mut head=HEAD::new();
+    head.currentBranchName=String::from("master");
+    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    head
+}
+
+//从root下的./.gitc/maps中读出head，还没有提交过的仓库返回只有master分支的head，同时读出配置。
+//返回的objects只是空的缓存，对象在用到时才从./.gitc/objects中读出
+pub fn loadState(root:&Path)->Result<(Objects,HEAD),GitcError>
+{
+    if !root.join(".gitc").is_dir()
     {
-        let mut newbranch=Branch::new(branchname.clone());
+        return Err(GitcError::NotARepository);
+    }
+    let config=Config::load(Some(root))?;
+    let mut objects=Objects::at(root);
+    objects.compression=config.compression()?;
+    let (objects,mut head)=loadHead(root,objects)?;
+    head.config=config;
+    Ok((objects,head))
+}
 
-        for (key,value) in head.branch.get(&head.currentBranchName).cloned().unwrap().borrow().references.borrow().refermap.clone()
+fn loadHead(root:&Path,objects:Objects)->Result<(Objects,HEAD),GitcError>
+{
+    //没有版本文件的是最早的版本1
+    let version:u32=fs::read_to_string(root.join(VERSION_FILE)).unwrap_or_default().trim().parse().unwrap_or(1);
+    if version==STORE_VERSION
+    {
+        let mut head=if root.join(HEAD_FILE).exists()
         {
-            newbranch.references.borrow_mut().refermap.insert(key,value);
+            deserialize(&fs::read(root.join(HEAD_FILE))?)?
         }
-        newbranch.commitpointer=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow().commitpointer.clone();
-        head.branch.insert(branchname.clone(),Rc::new(RefCell::new(newbranch)));
-        if persistence
+        else {
+            newHead()
+        };
+        head.index=Index::load(root)?;
+        return Ok((objects,head));
+    }
+
+    let mut head=migrateHead(root,version)?;
+    if version>=4
+    {
+        head.index=Index::load(root)?;
+    }
+    else
+    {
+        head.index.root=root.to_path_buf();
+        migrateIndex(root,&mut head)?;
+    }
+    //references中剩下的都是以提交信息为名的commit，它们仍然可以从分支找到
+    for branch in head.branch.values()
+    {
+        branch.borrow().references.borrow_mut().refermap.clear();
+    }
+    saveHead(root,&mut head)?;
+    Ok((objects,head))
+}
+
+//版本7之前的head中分支直接嵌套保存commit、tree和blob，这里把它们写入./.gitc/objects，分支改为记录commit的SHA1
+fn migrateHead(root:&Path,version:u32)->Result<HEAD,GitcError>
+{
+    let renamed=if version<3 {migrateStore(root)?} else {BTreeMap::new()};
+    if !root.join(HEAD_FILE).exists()
+    {
+        return Ok(newHead());
+    }
+    let legacy:LegacyHEAD=deserialize(&fs::read(root.join(HEAD_FILE))?)?;
+    let mut head=legacy.upgrade(&mut Objects::at(root))?;
+
+    //版本1的SHA1在migrateStore中重新计算过，references和mergeHead也要换成新的SHA1
+    let rename=|id:&mut String|
+    {
+        if let Some(new)=renamed.get(id)
         {
-            let mut f_head=File::create("./.gitc/maps/head");
-            f_head.unwrap().write(&serialize(&head).unwrap());
+            *id=new.clone();
         }
+    };
+    for branch in head.branch.values()
+    {
+        let reference=branch.borrow().references.clone();
+        reference.borrow_mut().refermap.values_mut().for_each(rename);
     }
-    else {
-        println!("This name is contained.Try another name");
+    if let Some(id)=head.mergeHead.as_mut()
+    {
+        rename(id);
     }
+    Ok(head)
 }
 
+//把旧版本整体写在./.gitc/maps/objs中的对象拆分写入./.gitc/objects，返回旧SHA1到新SHA1的映射。
+//版本1的SHA1由bincode序列化结果计算(包含路径，且每个字节的高低4位顺序颠倒)，写入时都按照git的方式重新计算
+fn migrateStore(root:&Path)->Result<BTreeMap<String,String>,GitcError>
+{
+    let objs=root.join(OBJS_FILE);
+    let objects:LegacyObjects=if objs.exists()
+    {
+        deserialize(&fs::read(&objs)?)?
+    }
+    else {
+        LegacyObjects{treemap:BTreeMap::new(),commitmap:BTreeMap::new(),blobmap:BTreeMap::new()}
+    };
+    //旧版本的gitAdd会把bincode格式的blob写进./.gitc/objects，这些文件都可以从maps/objs中重新生成
+    let objectsDir=root.join(OBJECTS_DIR);
+    if objectsDir.is_dir()
+    {
+        fs::remove_dir_all(&objectsDir)?;
+    }
+    fs::create_dir_all(&objectsDir)?;
+    let renamed=objects.store(&mut Objects::at(root))?;
+    if objs.exists()
+    {
+        fs::remove_file(&objs)?;
+    }
+    Ok(renamed)
+}
 
-pub fn gitCheckout(head:&mut HEAD,branchname:String,persistence:bool)
+//版本4之前的暂存区就是当前分支references中记录的文件，这里把它们移到./.gitc/index中
+fn migrateIndex(root:&Path,head:&mut HEAD)->Result<(),GitcError>
 {
-    if head.branch.contains_key(&branchname)
+    let objects=Objects::at(root);
+    let reference=head.currentBranch()?.borrow().references.clone();
+    for (path,id) in reference.borrow().refermap.iter()
     {
-        head.currentBranchName=branchname;
-        if persistence
+        if objects.kindOf(id)==Some(objecttype::blob)
         {
-            let mut f_head=File::create("./.gitc/maps/head");
-            f_head.unwrap().write(&serialize(&head).unwrap());
+            head.index.record(path,id,MODE_FILE)?;
         }
     }
-    else {
-        println!("uncontained name!");
+    Ok(())
+}
+
+//对象在写入时已经存进./.gitc/objects，这里只需要把head和暂存区保存到root下的./.gitc中
+pub fn saveHead(root:&Path,head:&mut HEAD)->Result<(),GitcError>
+{
+    let temp=root.join(HEAD_FILE).with_extension("tmp");
+    let mut f_head=File::create(&temp)?;
+    f_head.write_all(&serialize(&head)?)?;
+    fs::rename(&temp,root.join(HEAD_FILE))?;
+    head.index.save()?;
+    fs::write(root.join(VERSION_FILE),STORE_VERSION.to_string())?;
+    Ok(())
+}
+
+pub fn gitBranch(repo:&mut Repository,branchname:String,persistence:bool)->Result<(),GitcError>
+{
+    let Repository{root,head,..}=repo;
+    head.addbranch(branchname)?;
+    if persistence
+    {
+        saveHead(root,head)?;
     }
+    Ok(())
 }
 
-pub fn gitMerge(head:&mut HEAD,branch2:String,message:&str,author:&str,objects:&mut Objects,persistence:bool)
+
+//工作区中的path是否正好是version(blob的SHA1和mode)，None表示文件不存在
+fn matchesDisk(root:&Path,path:&str,version:Option<&(String,u32)>)->Result<bool,GitcError>
 {
+    let file=root.join(path);
+    Ok(match version
+    {
+        Some((id,mode))=>file.is_file() && sameMode(&fs::metadata(&file)?,*mode) && hashBlob(&fs::read(&file)?)==*id,
+        None=>!file.is_file(),
+    })
+}
 
-    let mut reference=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-    let mut mainbranch=head.branch.get(&head.currentBranchName).cloned().unwrap();
-    let mut minorbranch=head.branch.get(&branch2).cloned().unwrap();
+//工作区中的文件相对当前commit是否有还没提交的改动(工作区或暂存区中的内容或mode和commit中的不同)
+fn hasLocalChanges(root:&Path,path:&str,committed:Option<&(String,u32)>,index:&Index)->Result<bool,GitcError>
+{
+    if !matchesDisk(root,path,committed)?
+    {
+        return Ok(true);
+    }
+    let staged=index.get(path).map(|entry| (entry.id.clone(),entry.mode));
+    Ok(staged.as_ref()!=committed)
+}
 
-    let mut maincommit=mainbranch.borrow().commitpointer.clone().unwrap();
-    let mut minorcommit=minorbranch.borrow().commitpointer.clone().unwrap();
+//from和to两个快照中内容或mode不同的文件
+fn changedPaths(from:&BTreeMap<String,(String,u32)>,to:&BTreeMap<String,(String,u32)>)->Vec<String>
+{
+    let paths:BTreeSet<&String>=from.keys().chain(to.keys()).collect();
+    paths.into_iter().filter(|path| from.get(*path)!=to.get(*path)).cloned().collect()
+}
 
-    let mut newcommit=Commit::new();
-    newcommit.t=objecttype::commit;
-    newcommit.message=String::from(message);
-    newcommit.author=String::from(author);
-    for c in 0..maincommit.borrow().parents.len()
+//把工作区中的文件改成targets中的版本(None表示删除)时会丢掉没提交的改动的文件：
+//工作区或暂存区中的版本和当前commit(current)中的不同，并且工作区中的内容也不是要写入的版本
+fn overwrittenPaths(root:&Path,targets:&BTreeMap<String,Option<(String,u32)>>,current:&BTreeMap<String,(String,u32)>,index:&Index)->Result<Vec<String>,GitcError>
+{
+    let mut res=vec![];
+    for (path,target) in targets.iter()
     {
-        if let Some(son)=maincommit.borrow().parents.get(c).cloned()
+        if !matchesDisk(root,path,target.as_ref())? && hasLocalChanges(root,path,current.get(path),index)?
         {
-            newcommit.parents.push(son);
+            res.push(path.clone());
         }
     }
-    newcommit.parents.push(Some(maincommit.clone()));
-    for c in 0..minorcommit.borrow().parents.len()
+    Ok(res)
+}
+
+//把changed中的文件在工作区和暂存区中都改成target中的版本，target中没有的文件删除，其他文件上暂存的改动保留
+fn applyChanges(root:&Path,objects:&Objects,index:&mut Index,changed:&[String],target:&BTreeMap<String,(String,u32)>)->Result<(),GitcError>
+{
+    for path in changed.iter()
     {
-        if let Some(son)=minorcommit.borrow().parents.get(c).cloned()
+        match target.get(path)
         {
-            newcommit.mergeparents.push(son);
+            Some((id,mode))=>
+            {
+                writeWorkingFile(root,path,&objects.blobContents(id)?,*mode)?;
+                index.record(path,id,*mode)?;
+            }
+            None=>
+            {
+                removeWorkingFile(root,path)?;
+                index.remove(path);
+            }
         }
     }
-    newcommit.mergeparents.push(Some(minorcommit.clone()));
-    newcommit.snapshot.borrow_mut().name=String::from(".");
-    newcommit.snapshot.borrow_mut().t=objecttype::tree;
-    newcommit.snapshot.borrow_mut().blobs=maincommit.borrow().snapshot.borrow().blobs.clone();
-    newcommit.snapshot.borrow_mut().trees=maincommit.borrow().snapshot.borrow().trees.clone();
+    Ok(())
+}
 
-    let mut whilecount:usize=0;
-    let mut mainCurrentTreeNode=newcommit.snapshot.clone();
-    let mut minorCurrentTreeNode=minorcommit.borrow_mut().snapshot.clone();
-    let mut mainTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    mainTreeQueue.push(mainCurrentTreeNode.clone());
-    let mut minorTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    minorTreeQueue.push(minorCurrentTreeNode.clone());
-    while minorTreeQueue.len()!=0
+//切换分支并把目标分支commit的快照写入工作区。
+//会被覆盖的文件如果有没提交的改动则拒绝切换，force为true时直接丢弃这些改动
+pub fn gitCheckout(repo:&mut Repository,branchname:String,force:bool,persistence:bool)->Result<(),GitcError>
+{
+    let Repository{root,head,objects,..}=repo;
+    let target=head.branch.get(&branchname).cloned().ok_or_else(|| GitcError::UnknownBranch(branchname.clone()))?;
+    let current=head.currentBranch()?;
+    if head.mergeHead.is_some() && !force
     {
-        if whilecount==0
-        {
-            mainTreeQueue.remove(0);
-            minorTreeQueue.remove(0);
-        }
-        else if whilecount>0
+        return Err(GitcError::MergeInProgress);
+    }
+    let currentFiles=commitBlobs(objects,current.borrow().commitpointer.as_deref())?;
+    let targetFiles=commitBlobs(objects,target.borrow().commitpointer.as_deref())?;
+    //两个快照中不同的文件才会被改写
+    let changed=changedPaths(&currentFiles,&targetFiles);
+    if !force
+    {
+        let targets=changed.iter().map(|path| (path.clone(),targetFiles.get(path).cloned())).collect();
+        let overwritten=overwrittenPaths(root,&targets,&currentFiles,&head.index)?;
+        if !overwritten.is_empty()
         {
-            mainCurrentTreeNode=mainTreeQueue.get(0).cloned().unwrap();
-            minorCurrentTreeNode=minorTreeQueue.get(0).cloned().unwrap();
-            mainTreeQueue.remove(0);
-            minorTreeQueue.remove(0);
+            return Err(GitcError::WouldOverwrite(String::from("checkout"),overwritten));
         }
-        for (key,value) in minorCurrentTreeNode.borrow().blobs.clone()
+    }
+    applyChanges(root,objects,&mut head.index,&changed,&targetFiles)?;
+    if force
+    {
+        //丢弃两个快照中相同的文件上的本地改动
+        for (path,version) in targetFiles.iter()
         {
-            //println!("{:?},{:?}",key,value);
-            if(mainCurrentTreeNode.borrow().blobs.contains_key(&key))  //也有这个文件 涉及合并问题 之后再搞
+            if !matchesDisk(root,path,Some(version))?
             {
-
-            }
-            else  //main branch 中没有 直接加入
-            {
-
-                mainCurrentTreeNode.borrow_mut().blobs.insert(key.clone(),value.clone());
-                let SHA1id_temp=getSHA1(&serialize(&value.clone()).unwrap());
-                objects.blobmap.insert(SHA1id_temp.clone(),value.clone());
-                reference.borrow_mut().refermap.insert(key.clone(),SHA1id_temp.clone());
+                writeWorkingFile(root,path,&objects.blobContents(&version.0)?,version.1)?;
             }
         }
-        for (key,value) in minorCurrentTreeNode.borrow().trees.clone()
+        head.index.entries.retain(|path,_| targetFiles.contains_key(path));
+        for (path,(id,mode)) in targetFiles.iter()
         {
-            if(mainCurrentTreeNode.borrow().trees.contains_key(&key))  //都有这个文件夹就进去
-            {
-                minorTreeQueue.push(value.clone());
-                mainTreeQueue.push(mainCurrentTreeNode.borrow().trees.get(&key).cloned().unwrap());
-            }
-            else
-            {
-                mainCurrentTreeNode.borrow_mut().trees.insert(key,value);
-            }
+            head.index.record(path,id,*mode)?;
         }
-        whilecount+=1;
+        head.mergeHead=None;
+        head.index.unmerged.clear();
     }
 
-    //对所有文件夹的 SHA进行一次更新
-    let mut renewcount:usize=0;
-    let mut renewCurrentTreeNode=newcommit.snapshot.clone();
-    let mut renewTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    let mut recordTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    let mut recordblobQueue:Vec<Rc<RefCell<blob>>>=vec![];
-    renewTreeQueue.push(renewCurrentTreeNode.clone());
-    recordTreeQueue.push(renewCurrentTreeNode.clone());
+    head.checkout(branchname)?;
+    if persistence
+    {
+        saveHead(root,head)?;
+    }
+    Ok(())
+}
+
+//gitMerge遇到可以快进的情况时的处理方式，对应命令行的默认行为、--no-ff和--ff-only
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum MergeMode
+{
+    FastForward,
+    NoFastForward,
+    FastForwardOnly,
+}
 
-    while renewTreeQueue.len()!=0
+impl MergeMode {
+    //命令行没有给出--no-ff或--ff-only时按merge.ff：false相当于--no-ff，only相当于--ff-only
+    pub fn fromConfig(config:&Config)->Result<MergeMode,GitcError>
     {
-        if renewcount==0
+        if config.get("merge.ff").as_deref()==Some("only")
         {
-            renewTreeQueue.remove(0);
+            return Ok(MergeMode::FastForwardOnly);
         }
-        else if renewcount>0
+        match config.getBool("merge.ff")?
         {
-            renewCurrentTreeNode=renewTreeQueue.get(0).cloned().unwrap();
-            renewTreeQueue.remove(0);
+            Some(false)=>Ok(MergeMode::NoFastForward),
+            _=>Ok(MergeMode::FastForward),
         }
-        for (key,value) in renewCurrentTreeNode.borrow().blobs.clone()
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum MergeOutcome
+{
+    //被合并的分支已经包含在当前分支中，什么都不用做
+    UpToDate,
+    //当前分支是被合并分支的祖先，只移动了commitpointer
+    FastForward,
+    //生成了新的merge commit
+    Merged,
+    //有冲突，没有生成commit
+    Conflicted,
+}
+
+//gitMerge的结果，conflicts为合并时产生冲突、需要手动解决的文件
+#[derive(Debug,PartialEq)]
+pub struct MergeResult
+{
+    pub outcome:MergeOutcome,
+    pub conflicts:Vec<String>,
+}
+
+//用路径到blob SHA1和mode的map重新搭建一棵以"."为根的tree，从最深的文件夹开始写入所有的tree，返回根tree的SHA1
+pub fn buildSnapshot(files:&BTreeMap<String,(String,u32)>,objects:&mut Objects)->Result<String,GitcError>
+{
+    let mut dirs:BTreeMap<String,tree>=BTreeMap::new();
+    dirs.insert(String::from("."),tree::new(String::from(".")));
+    for (path,(id,mode)) in files
+    {
+        let mut dir=fatherName(path);
+        let node=dirs.entry(dir.clone()).or_insert_with(|| tree::new(dir.clone()));
+        node.blobs.insert(path.clone(),id.clone());
+        if *mode!=MODE_FILE
         {
-            recordblobQueue.push(value.clone());
+            node.modes.insert(path.clone(),*mode);
         }
-        for (key,value) in renewCurrentTreeNode.borrow().trees.clone()
+        //逐级向上补上缺少的文件夹
+        while dir!="."
         {
-            recordTreeQueue.push(value.clone());
+            dir=fatherName(&dir);
+            dirs.entry(dir.clone()).or_insert_with(|| tree::new(dir.clone()));
         }
-        renewcount+=1;
     }
-    for index in 0..recordblobQueue.len()
+    let mut names:Vec<String>=dirs.keys().cloned().collect();
+    names.sort_by_key(|name| std::cmp::Reverse(name.matches('/').count()));
+    let mut root=String::new();
+    for name in names
     {
-        let obj=recordblobQueue.get(index).cloned().unwrap();
-        let SHA1id_temp=getSHA1(&serialize(&obj).unwrap());
-        objects.blobmap.insert(SHA1id_temp.clone(),obj.clone());
-        reference.borrow_mut().refermap.insert(obj.clone().borrow().name.clone(),SHA1id_temp.clone());
+        let Some(node)=dirs.remove(&name) else {continue};
+        let id=objects.insertTree(&Rc::new(RefCell::new(node)))?;
+        if name=="."
+        {
+            root=id;
+        }
+        else if let Some(father)=dirs.get_mut(&fatherName(&name))
+        {
+            father.trees.insert(name,id);
+        }
     }
+    Ok(root)
+}
 
-    for index in 0..recordTreeQueue.len()
+//path为相对工作区根目录root的"./xxx"形式，mode为tree中记录的mode
+pub fn writeWorkingFile(root:&Path,path:&str,contents:&[u8],mode:u32)->Result<(),GitcError>
+{
+    let file=root.join(path);
+    if let Some(dir)=file.parent()
     {
-        let obj=recordTreeQueue.get(index).cloned().unwrap();
-        let SHA1id_temp=getSHA1(&serialize(&obj).unwrap());
-
-        objects.treemap.insert(SHA1id_temp.clone(),obj.clone());
-        reference.borrow_mut().refermap.insert(obj.clone().borrow().name.clone(),SHA1id_temp.clone());
+        fs::create_dir_all(dir)?;
     }
+    fs::write(&file,contents)?;
+    setMode(&file,mode)
+}
 
-
-    let mut commit_rc=Rc::new(RefCell::new(newcommit));
-    let SHA1id_temp=getSHA1(&serialize(&commit_rc).unwrap());
-    objects.commitmap.insert(SHA1id_temp.clone(),commit_rc.clone());
-    reference.borrow_mut().refermap.insert(String::from(message),SHA1id_temp.clone());
-
-
-    head.branch.get(&head.currentBranchName.clone()).cloned().unwrap().borrow_mut().commitpointer=Some(commit_rc.clone());
-
-
-    if persistence
+//和git一样，可执行文件给有读权限的用户加上执行权限，普通文件去掉所有执行权限
+#[cfg(unix)]
+fn setMode(file:&Path,mode:u32)->Result<(),GitcError>
+{
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions=fs::metadata(file)?.permissions();
+    let bits=permissions.mode();
+    let wanted=if mode==MODE_EXECUTABLE {bits|(bits&0o444)>>2} else {bits&!0o111};
+    if wanted!=bits
     {
-        let mut f_obj=File::create("./.gitc/maps/objs");
-        f_obj.unwrap().write(&serialize(&objects).unwrap());
-
-        let mut f_head=File::create("./.gitc/maps/head");
-        f_head.unwrap().write(&serialize(&head).unwrap());
+        permissions.set_mode(wanted);
+        fs::set_permissions(file,permissions)?;
     }
+    Ok(())
 }
-pub fn gitCommit(head:&mut HEAD,objects:&mut Objects,message:&str, author:&str,persistence:bool)
-{
-    let mut reference=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-    let mut commit=Commit::new();
-    commit.message=String::from(message);
-    commit.author=String::from(author);
-
-    let snapshot=load_reference_tree(&reference,objects,String::from("."));
-
-    commit.snapshot=snapshot;
 
-    let currentbranch=head.branch.get(&head.currentBranchName.clone()).cloned().unwrap();
+#[cfg(not(unix))]
+fn setMode(_file:&Path,_mode:u32)->Result<(),GitcError>
+{
+    Ok(())
+}
 
-    let fathercommit=currentbranch.borrow().commitpointer.clone().unwrap();
-    if Some(fathercommit.clone()).is_some()
+//删除工作区中的文件，并删除因此变空的上级文件夹
+pub fn removeWorkingFile(root:&Path,path:&str)->Result<(),GitcError>
+{
+    let file=root.join(path);
+    if file.is_file()
     {
-        for c in 0..fathercommit.borrow().parents.len()
-        {
-            if let Some(son)=fathercommit.borrow().parents.get(c).cloned()
-            {
-                commit.parents.push(son);
-            }
-        }
-        if !fathercommit.borrow().message.eq(&String::from(""))
+        fs::remove_file(file)?;
+    }
+    let mut dir=fatherName(path);
+    while !dir.is_empty() && dir!="."
+    {
+        let dirPath=root.join(&dir);
+        if !dirPath.is_dir() || fs::read_dir(&dirPath)?.next().is_some()
         {
-            commit.parents.push(Some(fathercommit));
+            break;
         }
-
+        fs::remove_dir(&dirPath)?;
+        dir=fatherName(&dir);
     }
+    Ok(())
+}
 
-    let mut commit_rc=Rc::new(RefCell::new(commit));
-
-    let SHA1id_temp=getSHA1(&serialize(&commit_rc).unwrap());
-    objects.commitmap.insert(SHA1id_temp.clone(),commit_rc.clone());
-    reference.borrow_mut().refermap.insert(String::from(message),SHA1id_temp.clone());
-
-    let mut branchname=head.currentBranchName.clone();
-
-    head.branch.get(&branchname).cloned().unwrap().borrow_mut().commitpointer=Some(commit_rc.clone());
-
-    if persistence
-    {
-        let mut f_obj=File::create("./.gitc/maps/objs");
-        f_obj.unwrap().write(&serialize(&objects).unwrap());
-
-        let mut f_head=File::create("./.gitc/maps/head");
-        f_head.unwrap().write(&serialize(&head).unwrap());
-    }
+//三方合并两组文件的结果：files为合并后要暂存的文件，worktree为需要写入(Some)或删除(None)的工作区文件
+struct TreeMerge
+{
+    files:BTreeMap<String,(String,u32)>,
+    worktree:BTreeMap<String,Option<(Vec<u8>,u32)>>,
+    conflicts:Vec<String>,
 }
 
-pub fn gitAdd(path:String,objects:&mut Objects,head:&mut HEAD,persistence:bool)
+//以base为共同祖先逐个文件合并ours和theirs：
+//只有一边改动的文件直接采用改动，两边都改动的文件按行合并并写入objects，重叠的改动写成冲突标记留在工作区
+fn mergeTrees(objects:&mut Objects,base:&BTreeMap<String,(String,u32)>,ours:&BTreeMap<String,(String,u32)>,theirs:&BTreeMap<String,(String,u32)>,oursLabel:&str,theirsLabel:&str,style:ConflictStyle)->Result<TreeMerge,GitcError>
 {
-    let mut reference= head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-    let p=Path::new(&(path));
-    if p.is_file()
+    let mut paths:BTreeSet<String>=BTreeSet::new();
+    paths.extend(base.keys().cloned());
+    paths.extend(ours.keys().cloned());
+    paths.extend(theirs.keys().cloned());
+
+    let mut res=TreeMerge{files:BTreeMap::new(),worktree:BTreeMap::new(),conflicts:vec![]};
+    for path in paths
     {
-        let mut file=blob::new(path.clone());
-        file.getContents(path.clone());
-        let file_rc=Rc::new(RefCell::new(file));
-        let SHA1id=getSHA1(&serialize(&file_rc).unwrap());
-        if !objects.blobmap.contains_key(&SHA1id)
+        let baseVersion=base.get(&path);
+        let oursVersion=ours.get(&path);
+        let theirsVersion=theirs.get(&path);
+        if oursVersion==theirsVersion || theirsVersion==baseVersion
         {
-            objects.blobmap.insert(SHA1id.clone(),file_rc.clone());
-            reference.borrow_mut().refermap.insert(path.clone(),SHA1id.clone());
-            let part1=&SHA1id[0..2];
-            let part2=&SHA1id[2..40];
-            let mut dir_path="./.gitc/objects/".to_string();
-            let path1=dir_path+&String::from(part1);
-            let dir=Path::new(&path1);
-            if dir.exists()==false
+            if let Some(oursVersion)=oursVersion
             {
-                fs::create_dir(&path1);
+                res.files.insert(path,oursVersion.clone());
             }
-            let path_temp=path1+&String::from("/");
-            let path2=path_temp+&String::from(part2);
-            let mut f=File::create(&path2);
-            f.unwrap().write(&serialize(&file_rc).unwrap());
-            let mut fathername=fatherName(&(path));
-            let mut sonname=path.clone();
-            let mut sonpath=p.clone();
-            let mut sonobj=Rc::new(RefCell::new(tree::new(String::from(""))));
-            while !fathername.eq("")
+            continue;
+        }
+        if oursVersion==baseVersion
+        {
+            match theirsVersion
             {
-                let mut tree_temp_rc=Rc::new(RefCell::new(tree::new(fathername.clone())));
-
-                if reference.borrow_mut().refermap.contains_key(&fathername)
+                Some((theirsId,mode))=>
                 {
-                    let mut temp=load_reference_tree(&reference,objects,fathername.clone());
-                    for (key,value) in temp.borrow().blobs.clone()
-                    {
-                        tree_temp_rc.borrow_mut().blobs.insert(key,value);
-                    }
-                    for (key,value) in temp.borrow().trees.clone()
-                    {
-                        tree_temp_rc.borrow_mut().trees.insert(key,value);
-                    }
+                    res.worktree.insert(path.clone(),Some((objects.blobContents(theirsId)?,*mode)));
+                    res.files.insert(path,(theirsId.clone(),*mode));
                 }
-                if sonpath.clone().is_file()
+                None=>
+                {
+                    res.worktree.insert(path,None);
+                }
+            }
+            continue;
+        }
+        match (oursVersion,theirsVersion)
+        {
+            (Some((oursId,oursMode)),Some((theirsId,theirsMode)))=>
+            {
+                //只有一边改了mode时采用改动后的mode，两边都改了时保留当前分支的
+                let mode=if baseVersion.map(|(_,mode)| mode)==Some(oursMode) {*theirsMode} else {*oursMode};
+                let baseContents=match baseVersion
                 {
-                    tree_temp_rc.borrow_mut().blobs.insert(sonname.clone(),file_rc.clone());
+                    Some((baseId,_))=>objects.blobContents(baseId)?,
+                    None=>vec![],
+                };
+                let file=merge3(&baseContents,&objects.blobContents(oursId)?,&objects.blobContents(theirsId)?,oursLabel,theirsLabel,style);
+                res.worktree.insert(path.clone(),Some((file.contents.clone(),mode)));
+                if file.conflicts>0
+                {
+                    res.files.insert(path.clone(),(oursId.clone(),*oursMode));
+                    res.conflicts.push(path);
                 }
                 else
                 {
-                    tree_temp_rc.borrow_mut().trees.insert(sonname.clone(),sonobj.clone());
-
+                    let mut newblob=blob::new(path.clone());
+                    newblob.contents=file.contents;
+                    let id=objects.insertBlob(&Rc::new(RefCell::new(newblob)))?;
+                    res.files.insert(path,(id,mode));
                 }
-                sonname=fathername.clone();
-                sonpath=Path::new(&sonname);
-                fathername=fatherName(&(sonname));
-                sonobj=tree_temp_rc.clone();
-                let SHA1id_temp=getSHA1(&serialize(&sonobj).unwrap());
-                objects.treemap.insert(SHA1id_temp.clone(),sonobj.clone());
-                reference.borrow_mut().refermap.insert(sonname.clone(),SHA1id_temp.clone());
             }
-            if persistence
+            //一边修改一边删除，保留修改后的文件等待手动处理
+            (Some(oursVersion),None)=>
             {
-                let mut f_obj=File::create("./.gitc/maps/objs");
-                f_obj.unwrap().write(&serialize(&objects).unwrap());
+                res.files.insert(path.clone(),oursVersion.clone());
+                res.conflicts.push(path);
+            }
+            (None,Some((theirsId,mode)))=>
+            {
+                res.worktree.insert(path.clone(),Some((objects.blobContents(theirsId)?,*mode)));
+                res.conflicts.push(path);
+            }
+            (None,None)=>{}
+        }
+    }
+    Ok(res)
+}
 
-                let mut f_head=File::create("./.gitc/maps/head");
-                f_head.unwrap().write(&serialize(&head).unwrap());
+//合并时作为base的文件。交叉合并时有多个最佳共同祖先，
+//像git的recursive策略一样先把这些祖先两两合并成一个虚拟的base，其中的冲突标记原样保留
+fn mergeBaseFiles(objects:&mut Objects,bases:&[String])->Result<BTreeMap<String,(String,u32)>,GitcError>
+{
+    let first=match bases.first()
+    {
+        Some(first)=>first,
+        None=>return Ok(BTreeMap::new()),
+    };
+    let mut files=commitBlobs(objects,Some(first))?;
+    for other in bases[1..].iter()
+    {
+        let innerBases=mergeBases(objects,first,other)?;
+        let innerBase=mergeBaseFiles(objects,&innerBases)?;
+        let otherFiles=commitBlobs(objects,Some(other))?;
+        let merged=mergeTrees(objects,&innerBase,&files,&otherFiles,"Temporary merge branch 1","Temporary merge branch 2",ConflictStyle::Merge)?;
+        files=merged.files;
+        for path in merged.conflicts
+        {
+            if let Some(Some((contents,mode)))=merged.worktree.get(&path)
+            {
+                let mut virtualblob=blob::new(path.clone());
+                virtualblob.contents=contents.clone();
+                files.insert(path,(objects.insertBlob(&Rc::new(RefCell::new(virtualblob)))?,*mode));
             }
         }
     }
+    Ok(files)
 }
-pub fn gitRm(path:String,objects:&mut Objects,head:&mut HEAD,persistence:bool)
+
+//以共同祖先为base对两个分支的每个文件做三方合并，有冲突时不生成commit，
+//冲突的文件带着冲突标记(按merge.conflictstyle的写法)写入工作区，解决后再次提交时会把被合并的commit作为mergeparent
+pub fn gitMerge(repo:&mut Repository,branch2:String,message:&str,author:&str,mode:MergeMode,persistence:bool)->Result<MergeResult,GitcError>
 {
-    let mut reference=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-    let p=Path::new(&(path));
-    if p.is_file()
+    let Repository{root,head,objects,..}=repo;
+    if head.mergeHead.is_some()
+    {
+        return Err(GitcError::MergeInProgress);
+    }
+    let mainbranch=head.currentBranch()?;
+    let minorbranch=head.branch.get(&branch2).cloned().ok_or_else(|| GitcError::UnknownBranch(branch2.clone()))?;
+
+    let maincommit=mainbranch.borrow().commitpointer.clone();
+    let minorcommit=minorbranch.borrow().commitpointer.clone().ok_or_else(|| GitcError::ObjectNotFound(branch2.clone()))?;
+
+    if let Some(maincommit)=maincommit.as_ref()
     {
-        let filename=path.clone();
-        let mut file=load_reference(&reference,objects,filename);
-        let SHA1id=getSHA1(&serialize(&file).unwrap());
-        if objects.blobmap.contains_key(&SHA1id)
+        if isAncestor(objects,&minorcommit,maincommit)?
         {
-            objects.blobmap.remove(&SHA1id);
+            return Ok(MergeResult{outcome:MergeOutcome::UpToDate,conflicts:vec![]});
         }
-        if reference.borrow_mut().refermap.contains_key(&path)
+    }
+    //当前分支还没有提交过，或者是被合并分支的祖先，可以直接快进
+    let canFastForward=match maincommit.as_ref()
+    {
+        Some(maincommit)=>isAncestor(objects,maincommit,&minorcommit)?,
+        None=>true,
+    };
+    let oursFiles=commitBlobs(objects,maincommit.as_deref())?;
+    let theirsFiles=commitBlobs(objects,Some(&minorcommit))?;
+    if canFastForward && mode!=MergeMode::NoFastForward
+    {
+        //和gitCheckout一样，只改写两个快照中不同的文件，这些文件有没提交的改动时拒绝快进
+        let changed=changedPaths(&oursFiles,&theirsFiles);
+        let targets=changed.iter().map(|path| (path.clone(),theirsFiles.get(path).cloned())).collect();
+        let overwritten=overwrittenPaths(root,&targets,&oursFiles,&head.index)?;
+        if !overwritten.is_empty()
+        {
+            return Err(GitcError::WouldOverwrite(String::from("merge"),overwritten));
+        }
+        applyChanges(root,objects,&mut head.index,&changed,&theirsFiles)?;
+        mainbranch.borrow_mut().commitpointer=Some(minorcommit);
+        if persistence
+        {
+            saveHead(root,head)?;
+        }
+        return Ok(MergeResult{outcome:MergeOutcome::FastForward,conflicts:vec![]});
+    }
+    if mode==MergeMode::FastForwardOnly
+    {
+        return Err(GitcError::NonFastForward(branch2));
+    }
+
+    let bases=match maincommit.as_ref()
+    {
+        Some(maincommit)=>mergeBases(objects,maincommit,&minorcommit)?,
+        None=>vec![],
+    };
+    let baseFiles=mergeBaseFiles(objects,&bases)?;
+    let treeMerge=mergeTrees(objects,&baseFiles,&oursFiles,&theirsFiles,"HEAD",&branch2,head.config.conflictStyle()?)?;
+    //写入任何文件之前先检查，要被合并结果改写或删除的文件不能有没提交的改动
+    let targets=treeMerge.worktree.iter().map(|(path,contents)| (path.clone(),contents.as_ref().map(|(contents,mode)| (hashBlob(contents),*mode)))).collect();
+    let overwritten=overwrittenPaths(root,&targets,&oursFiles,&head.index)?;
+    if !overwritten.is_empty()
+    {
+        return Err(GitcError::WouldOverwrite(String::from("merge"),overwritten));
+    }
+    for (path,contents) in treeMerge.worktree.iter()
+    {
+        match contents
         {
-            reference.borrow_mut().refermap.remove(&path);
+            Some((contents,mode))=>writeWorkingFile(root,path,contents,*mode)?,
+            None=>removeWorkingFile(root,path)?,
         }
-        let mut fathername=fatherName(&(path));
-        let mut sonname=path.clone();
-        let mut sonpath=p.clone();
-        let mut sonobj=Rc::new(RefCell::new(tree::new(String::from(""))));
-        while !fathername.eq("")
+    }
+    let merged=treeMerge.files;
+    let mut result=MergeResult{outcome:MergeOutcome::Merged,conflicts:treeMerge.conflicts};
+
+    //暂存区中只改动合并改变了的文件，其他文件上暂存的改动保留
+    for path in changedPaths(&oursFiles,&merged)
+    {
+        match merged.get(&path)
         {
-            let tree_SHA1=reference.borrow_mut().refermap.get(&fathername).cloned().unwrap();
-            let mut tree_rc=objects.treemap.get(&tree_SHA1).cloned().unwrap(); //获取文件父亲树
-            let tree_temp=tree_rc.clone();
-            if sonpath.clone().is_file()
+            Some((id,mode))=>head.index.record(&path,id,*mode)?,
+            None=>
             {
-                tree_temp.borrow_mut().blobs.remove(&sonname.clone());
+                head.index.remove(&path);
             }
-            sonname=fathername.clone();
-            sonpath=Path::new(&sonname);
-            fathername=fatherName(&(sonname));
-            sonobj=tree_temp.clone();
-            let SHA1id_temp=getSHA1(&serialize(&sonobj).unwrap());
-            objects.treemap.insert(SHA1id_temp.clone(),sonobj.clone());
-            reference.borrow_mut().refermap.insert(sonname.clone(),SHA1id_temp.clone());
         }
+    }
+    let snapshot=buildSnapshot(&merged,objects)?;
+
+    if !result.conflicts.is_empty()
+    {
+        result.outcome=MergeOutcome::Conflicted;
+        head.mergeHead=Some(minorcommit);
+        head.index.unmerged.extend(result.conflicts.iter().cloned());
         if persistence
         {
-            let mut f_obj=File::create("./.gitc/maps/objs");
-            f_obj.unwrap().write(&serialize(&objects).unwrap());
-
-            let mut f_head=File::create("./.gitc/maps/head");
-            f_head.unwrap().write(&serialize(&head).unwrap());
+            saveHead(root,head)?;
         }
+        return Ok(result);
     }
-}
 
-pub fn gitInit()
+    let mut newcommit=Commit::new();
+    newcommit.t=objecttype::commit;
+    newcommit.message=String::from(message);
+    newcommit.author=Signature::author(author)?;
+    newcommit.committer=Signature::committer(&newcommit.author)?;
+    newcommit.parents.extend(maincommit);
+    newcommit.mergeparents.push(minorcommit);
+    newcommit.snapshot=snapshot;
+
+    let id=objects.insertCommit(&Rc::new(RefCell::new(newcommit)))?;
+    mainbranch.borrow_mut().commitpointer=Some(id);
+
+    if persistence
+    {
+        saveHead(root,head)?;
+    }
+    Ok(result)
+}
+//author为"名字 <邮箱>"，提交者默认和作者相同，两者都可以被GITC_AUTHOR_*和GITC_COMMITTER_*环境变量覆盖
+pub fn gitCommit(repo:&mut Repository,message:&str,author:&str,persistence:bool)->Result<(),GitcError>
 {
-    let target_path = Path::new("./.gitc");
-    if target_path.exists()==false
+    let Repository{root,head,objects,..}=repo;
+    //合并冲突的文件都重新暂存(或删除)之后才能提交
+    if !head.index.unmerged.is_empty()
     {
-        fs::create_dir("./.gitc");
-
+        return Err(GitcError::MergeInProgress);
     }
-    let head_path=Path::new("./.gitc/HEAD");
-    if  head_path.exists()==false
+    let currentbranch=head.currentBranch()?;
+    let mut commit=Commit::new();
+    commit.message=String::from(message);
+    commit.author=Signature::author(author)?;
+    commit.committer=Signature::committer(&commit.author)?;
+
+    //快照只由暂存区生成，还没有任何提交时暂存区不能为空
+    let files=head.index.blobs();
+    let father=currentbranch.borrow().commitpointer.clone();
+    if files.is_empty() && father.is_none()
     {
-        let mut f=File::create("./.gitc/HEAD");
-        f.unwrap().write(b"ref: refs/heads/master");
+        return Err(GitcError::NothingToCommit);
     }
-    let objects_path = Path::new("./.gitc/objects");
-    if objects_path.exists()==false
+    commit.snapshot=buildSnapshot(&files,objects)?;
+    commit.parents.extend(father);
+    //解决完合并冲突后的提交，把被合并的commit作为mergeparent
+    if let Some(mergeid)=head.mergeHead.take()
     {
-        fs::create_dir("./.gitc/objects");
+        commit.mergeparents.push(mergeid);
     }
-    let refs_path = Path::new("./.gitc/refs");
-    if refs_path.exists()==false
+
+    let id=objects.insertCommit(&Rc::new(RefCell::new(commit)))?;
+    currentbranch.borrow_mut().commitpointer=Some(id);
+
+    if persistence
     {
-        fs::create_dir("./.gitc/refs");
+        saveHead(root,head)?;
+    }
+    Ok(())
+}
 
+//把一个文件的当前内容写入objects并暂存。
+//不论blob是否已经存在(比如文件改回了之前的内容，或者和其他文件内容相同)，暂存区都指向当前的内容
+fn stageFile(root:&Path,path:&str,objects:&mut Objects,index:&mut Index)->Result<(),GitcError>
+{
+    //先取文件信息再读内容，读的时候文件又被修改的话修改时间会不同，下次比较时会重新计算SHA1
+    let metadata=fs::metadata(root.join(path))?;
+    let mut file=blob::new(path.to_string());
+    file.contents=fs::read(root.join(path))?;
+    let SHA1id=objects.insertBlob(&Rc::new(RefCell::new(file)))?;
+    index.stage(path,&SHA1id,Some(&metadata));
+    Ok(())
+}
+
+//暂存一个文件，path为文件夹时暂存其中的所有文件。没有暂存过的文件被.gitcignore忽略时不会暂存
+pub fn gitAdd(repo:&mut Repository,path:String,persistence:bool)->Result<(),GitcError>
+{
+    let Repository{root,head,objects,..}=repo;
+    let p=root.join(&path);
+    if !p.exists()
+    {
+        return Err(GitcError::PathNotFound(path));
     }
-    let refsh_path = Path::new("./.gitc/refs/heads");
-    if refsh_path.exists()==false
+    let rules=IgnoreRules::load(root)?;
+    let files=if p.is_file() {vec![path.clone()]} else {workingFiles(root,&path)?};
+    for file in files
     {
-        fs::create_dir("./.gitc/refs/heads");
-
+        if !head.index.contains(&file) && rules.isIgnored(&file,false)
+        {
+            if p.is_file()
+            {
+                return Err(GitcError::PathIgnored(path));
+            }
+            continue;
+        }
+        stageFile(root,&file,objects,&mut head.index)?;
     }
-    let refst_path = Path::new("./.gitc/refs/tags");
-    if refst_path.exists()==false
+    if persistence
     {
-        fs::create_dir("./.gitc/refs/tags");
-
+        saveHead(root,head)?;
     }
+    Ok(())
+}
 
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum AddMode
+{
+    //只暂存工作区中匹配的文件
+    Default,
+    //-A：同时把已经从工作区删除的文件暂存为删除
+    All,
+    //-u：只更新已经暂存过的文件，包括删除
+    Update,
 }
-pub fn load_reference(references:&Rc<RefCell<References>>,objects:&Objects,name_or_id:String)->Rc<RefCell<blob>>
+
+//path是否匹配pathspec："."、文件本身、文件夹下的文件或者通配符
+pub fn pathspecMatches(pathspec:&str,path:&str)->bool
+{
+    pathspec=="." || path==pathspec || path.starts_with(&format!("{}/",pathspec)) || (isGlob(pathspec) && wildmatch(pathspec,path,false))
+}
+
+//按pathspec暂存文件，pathspecs为空时表示整个工作区，返回被暂存(或暂存为删除)的文件
+pub fn gitAddPathspecs(repo:&mut Repository,pathspecs:&[String],mode:AddMode,persistence:bool)->Result<Vec<String>,GitcError>
 {
-    if references.borrow_mut().refermap.contains_key(&name_or_id)
+    let Repository{root,head,objects,..}=repo;
+    let pathspecs=if pathspecs.is_empty() {vec![String::from(".")]} else {pathspecs.to_vec()};
+    let tracked:BTreeSet<String>=head.index.entries.keys().cloned().collect();
+    let working=workingFiles(root,".")?;
+    let rules=IgnoreRules::load(root)?;
+
+    let mut added=BTreeSet::new();
+    let mut removed=BTreeSet::new();
+    for pathspec in pathspecs.iter()
+    {
+        let mut matched=false;
+        let mut ignored=false;
+        for path in working.iter().filter(|path| pathspecMatches(pathspec,path))
+        {
+            //已经暂存的文件不受忽略规则影响
+            if !tracked.contains(path) && rules.isIgnored(path,false)
+            {
+                ignored=true;
+                continue;
+            }
+            matched=true;
+            if mode!=AddMode::Update || tracked.contains(path)
+            {
+                added.insert(path.clone());
+            }
+        }
+        for path in tracked.iter().filter(|path| pathspecMatches(pathspec,path))
+        {
+            matched=true;
+            if mode!=AddMode::Default && !root.join(path).is_file()
+            {
+                removed.insert(path.clone());
+            }
+        }
+        if !matched && ignored && mode==AddMode::Default
+        {
+            return Err(GitcError::PathIgnored(pathspec.clone()));
+        }
+        if !matched && !ignored
+        {
+            return Err(GitcError::PathNotFound(pathspec.clone()));
+        }
+    }
+    //内容没有变化的文件不需要重新暂存
+    added.retain(|path| head.index.isModified(path).unwrap_or(true));
+    for path in added.iter()
     {
-        let mut res=objects.blobmap.get(references.borrow_mut().refermap.get(&name_or_id).unwrap()).cloned();
-        return res.unwrap();
+        stageFile(root,path,objects,&mut head.index)?;
     }
-    else
+    for path in removed.iter()
     {
-        let mut res=objects.blobmap.get(&name_or_id).cloned();
-        return res.unwrap();
+        head.index.remove(path);
     }
+    if persistence
+    {
+        saveHead(root,head)?;
+    }
+    Ok(added.into_iter().chain(removed).collect())
 }
 
-pub fn load_reference_tree(references:&Rc<RefCell<References>>,objects:&Objects,name_or_id:String)->Rc<RefCell<tree>>
+//从暂存区中删除文件(recursive为true时可以删除整个文件夹)，objects中的blob仍然留给之前的commit和其他分支使用。
+//cached为false时同时删除工作区中的文件
+//默认不删除有没提交改动的文件：暂存的版本和HEAD中的不同，或者工作区的文件和暂存的版本不同。
+//--cached只在暂存的版本和两者都不同时拒绝。force为true时不检查
+pub fn gitRm(repo:&mut Repository,path:String,cached:bool,recursive:bool,force:bool,persistence:bool)->Result<(),GitcError>
 {
-    if references.borrow_mut().refermap.contains_key(&name_or_id)
+    let Repository{root,head,objects,..}=repo;
+    let removed:Vec<String>=if head.index.contains(&path)
     {
-        let mut res=objects.treemap.get(references.borrow_mut().refermap.get(&name_or_id).unwrap()).cloned();
-        return res.unwrap();
+        vec![path.clone()]
     }
     else
     {
-        let mut res=objects.treemap.get(&name_or_id).cloned();
-        return res.unwrap();
+        let prefix=if path=="." {String::from("./")} else {format!("{}/",path)};
+        let under:Vec<String>=head.index.entries.keys().filter(|key| key.starts_with(&prefix)).cloned().collect();
+        if under.is_empty()
+        {
+            return Err(GitcError::ObjectNotFound(path));
+        }
+        if !recursive
+        {
+            return Err(GitcError::RecursiveRequired(path));
+        }
+        under
+    };
+    if !force
+    {
+        let headFiles=commitBlobs(objects,head.currentBranch()?.borrow().commitpointer.as_deref())?;
+        let mut modified=vec![];
+        for name in removed.iter()
+        {
+            let staged=head.index.get(name).map(|entry| (entry.id.clone(),entry.mode));
+            let stagedChanged=headFiles.get(name)!=staged.as_ref();
+            let worktreeChanged=root.join(name).is_file() && head.index.isModified(name)?;
+            let lost=if cached {stagedChanged && worktreeChanged} else {stagedChanged || worktreeChanged};
+            if lost
+            {
+                modified.push(name.clone());
+            }
+        }
+        if !modified.is_empty()
+        {
+            return Err(GitcError::LocalModifications(modified));
+        }
+    }
+    for name in removed.iter()
+    {
+        head.index.remove(name);
+        if !cached && root.join(name).is_file()
+        {
+            removeWorkingFile(root,name)?;
+        }
+    }
+    if persistence
+    {
+        saveHead(root,head)?;
     }
+    Ok(())
 }
 
+//在root下创建./.gitc
+pub fn gitInit(root:&Path)->Result<(),GitcError>
+{
+    fs::create_dir_all(root.join("./.gitc"))?;
+    let head_path=root.join("./.gitc/HEAD");
+    if !head_path.exists()
+    {
+        let mut f=File::create(&head_path)?;
+        f.write_all(b"ref: refs/heads/master")?;
+    }
+    fs::create_dir_all(root.join(OBJECTS_DIR))?;
+    fs::create_dir_all(root.join("./.gitc/refs/heads"))?;
+    fs::create_dir_all(root.join("./.gitc/refs/tags"))?;
+    fs::create_dir_all(root.join("./.gitc/maps"))?;
+    if !root.join(OBJS_FILE).exists() && !root.join(VERSION_FILE).exists()
+    {
+        fs::write(root.join(VERSION_FILE),STORE_VERSION.to_string())?;
+    }
+    Ok(())
+}
+pub fn load_reference(references:&Rc<RefCell<References>>,objects:&Objects,name_or_id:String)->Result<Rc<RefCell<blob>>,GitcError>
+{
+    let id=references.borrow().refermap.get(&name_or_id).cloned().unwrap_or(name_or_id.clone());
+    objects.loadBlob(&id,&name_or_id)
+}
 
-pub fn getSHA1(data:&[u8])->String
+pub fn load_reference_tree(references:&Rc<RefCell<References>>,objects:&Objects,name_or_id:String)->Result<Rc<RefCell<tree>>,GitcError>
 {
-    let mut hasher = Sha1::new();
-    hasher.update(data);
-    let mut res=String::new();
-    let result = hasher.finalize();
-    for i in result.iter()
+    match references.borrow().refermap.get(&name_or_id)
     {
-        let a1:u8=15;
-        let a2:u8=240;
-        let r1=i&a1;
-        let r2=(i&a2)/16;
+        Some(id)=>objects.loadTree(id,&name_or_id),
+        None=>objects.loadTree(&name_or_id,"."),
+    }
+}
+
+//收集一个tree及其所有子tree中的blob，key为blob的完整路径，value为blob的SHA1和mode
+pub fn snapshotEntries(objects:&Objects,root:&str)->Result<BTreeMap<String,(String,u32)>,GitcError>
+{
+    let mut res=BTreeMap::new();
+    let mut treeQueue:Vec<Rc<RefCell<tree>>>=vec![objects.loadTree(root,".")?];
+    while let Some(node)=treeQueue.pop()
+    {
+        for (key,value) in node.borrow().blobs.iter()
+        {
+            let mode=node.borrow().modes.get(key).copied().unwrap_or(MODE_FILE);
+            res.insert(key.clone(),(value.clone(),mode));
+        }
+        for (key,value) in node.borrow().trees.iter()
+        {
+            treeQueue.push(objects.loadTree(value,key)?);
+        }
+    }
+    Ok(res)
+}
 
-        res.push(Hex[r1 as usize]);
-        res.push(Hex[r2 as usize]);
+//和snapshotEntries相同，只保留blob的SHA1
+pub fn snapshotBlobs(objects:&Objects,root:&str)->Result<BTreeMap<String,String>,GitcError>
+{
+    Ok(snapshotEntries(objects,root)?.into_iter().map(|(path,(id,_))| (path,id)).collect())
+}
+
+//commit快照中的所有文件和它们的mode，id为None(分支还没有提交过)时为空
+pub fn commitBlobs(objects:&Objects,id:Option<&str>)->Result<BTreeMap<String,(String,u32)>,GitcError>
+{
+    match id
+    {
+        Some(id)=>
+        {
+            let snapshot=objects.loadCommit(id)?.borrow().snapshot.clone();
+            snapshotEntries(objects,&snapshot)
+        }
+        None=>Ok(BTreeMap::new()),
     }
-    return res;
-}
\ No newline at end of file
+}
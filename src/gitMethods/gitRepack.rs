@@ -0,0 +1,73 @@
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::objectStore::{objectPath,OBJECTS_DIR};
+use crate::gitUtils::pack::{writePack,verifyPack,PackSummary};
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+//root下./.gitc/objects中所有loose object的SHA1
+pub fn looseIds(root:&Path)->Result<Vec<String>,GitcError>
+{
+    let mut ids=vec![];
+    for entry in WalkDir::new(root.join(OBJECTS_DIR)).min_depth(2).max_depth(2).sort_by_file_name()
+    {
+        let entry=entry.map_err(|err| GitcError::Io(err.into()))?;
+        if !entry.file_type().is_file() || entry.path().extension().is_some()
+        {
+            continue;
+        }
+        let dir=entry.path().parent().and_then(|dir| dir.file_name()).map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let id=format!("{}{}",dir,entry.file_name().to_string_lossy());
+        if id.len()==40 && dir.len()==2
+        {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
+//把所有loose object和已有的pack按仓库的压缩级别合并成一个新的pack，之后删除这些loose object和旧的pack
+pub fn gitRepack(repo:&Repository)->Result<PackSummary,GitcError>
+{
+    let Repository{root,objects,..}=repo;
+    let loose=looseIds(root)?;
+    let oldPacks=objects.packs()?;
+    let mut ids:BTreeSet<String>=loose.iter().cloned().collect();
+    for pack in oldPacks.iter()
+    {
+        ids.extend(pack.entries.iter().map(|entry| entry.id.clone()));
+    }
+    if ids.is_empty()
+    {
+        return Ok(PackSummary::default());
+    }
+    let mut packed=vec![];
+    for id in ids.iter()
+    {
+        packed.push(objects.readObject(id)?);
+    }
+    let (packPath,summary)=writePack(root,&packed,objects.level())?;
+    objects.forgetPacks();
+    //新的pack缺少对象时保留原来的loose object和pack
+    verifyPack(&packPath,&ids)?;
+
+    for id in loose.iter()
+    {
+        let path=objectPath(root,id);
+        fs::remove_file(&path)?;
+        //文件夹空了就一起删掉
+        if let Some(dir)=path.parent()
+        {
+            let _=fs::remove_dir(dir);
+        }
+    }
+    for oldPack in oldPacks.iter().map(|pack| &pack.path).filter(|oldPack| **oldPack!=packPath)
+    {
+        fs::remove_file(oldPack.with_extension("idx"))?;
+        fs::remove_file(oldPack)?;
+    }
+    Ok(summary)
+}
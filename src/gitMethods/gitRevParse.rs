@@ -0,0 +1,117 @@
+use crate::gitUtils::gitUtils::HEAD;
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitUtils::Objects;
+use crate::gitUtils::gitUtils::objecttype;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitMethods::gitRepack::looseIds;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component,Path};
+
+//缩写的SHA1至少4位，和git相同
+pub const MIN_ABBREV:usize=4;
+
+//所有以prefix开头的commit的SHA1，包括loose object、pack和缓存中的
+fn commitsWithPrefix(root:&Path,objects:&Objects,prefix:&str)->Result<BTreeSet<String>,GitcError>
+{
+    let mut ids:BTreeSet<String>=looseIds(root)?.into_iter().filter(|id| id.starts_with(prefix)).collect();
+    for pack in objects.packs()?.iter()
+    {
+        ids.extend(pack.entries.iter().map(|entry| &entry.id).filter(|id| id.starts_with(prefix)).cloned());
+    }
+    ids.extend(objects.commitmap.keys().filter(|id| id.starts_with(prefix)).cloned());
+    ids.retain(|id| objects.kindOf(id)==Some(objecttype::commit));
+    Ok(ids)
+}
+
+//tag名只能是refs/tags下的相对路径，不能有根目录、盘符或".."
+fn isRefName(name:&str)->bool
+{
+    !name.is_empty() && Path::new(name).components().all(|component| matches!(component,Component::Normal(_)))
+}
+
+//"~"和"^"之前的部分：HEAD、tag、分支名、完整或缩写的SHA1
+fn resolveBase(root:&Path,head:&HEAD,objects:&Objects,name:&str)->Result<String,GitcError>
+{
+    let unknown=|| GitcError::UnknownRevision(name.to_string());
+    if name=="HEAD"
+    {
+        return head.currentBranch()?.borrow().commitpointer.clone().ok_or_else(unknown);
+    }
+    //和git一样，同名时tag优先于分支
+    let tag=root.join("./.gitc/refs/tags").join(name);
+    if isRefName(name) && tag.is_file()
+    {
+        let id=fs::read_to_string(&tag)?.trim().to_string();
+        if objects.kindOf(&id)!=Some(objecttype::commit)
+        {
+            return Err(unknown());
+        }
+        return Ok(id);
+    }
+    if let Some(branch)=head.branch.get(name)
+    {
+        return branch.borrow().commitpointer.clone().ok_or_else(unknown);
+    }
+    if name.len()<MIN_ABBREV || name.len()>40 || !name.bytes().all(|byte| byte.is_ascii_hexdigit())
+    {
+        return Err(unknown());
+    }
+    let ids=commitsWithPrefix(root,objects,&name.to_ascii_lowercase())?;
+    match ids.len()
+    {
+        0=>Err(unknown()),
+        1=>Ok(ids.into_iter().next().unwrap_or_default()),
+        _=>Err(GitcError::AmbiguousRevision(name.to_string())),
+    }
+}
+
+//"~"或"^"后面的数字，没有数字时为1
+fn suffixCount(digits:&str,rev:&str)->Result<usize,GitcError>
+{
+    if digits.is_empty()
+    {
+        return Ok(1);
+    }
+    digits.parse().map_err(|_| GitcError::UnknownRevision(rev.to_string()))
+}
+
+//把revision解析成commit的SHA1：<base>后面可以跟任意个"~n"(沿第一个父commit后退n次)和"^n"(第n个父commit，^0为自己)
+pub fn revParse(repo:&Repository,rev:&str)->Result<String,GitcError>
+{
+    let Repository{root,head,objects,..}=repo;
+    let unknown=|| GitcError::UnknownRevision(rev.to_string());
+    let split=rev.find(['~','^']).unwrap_or(rev.len());
+    let mut commit=resolveBase(root,head,objects,&rev[..split])?;
+    let mut rest=&rev[split..];
+    while let Some(op)=rest.chars().next()
+    {
+        if op!='~' && op!='^'
+        {
+            return Err(unknown());
+        }
+        let digits:String=rest[1..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        let count=suffixCount(&digits,rev)?;
+        rest=&rest[1+digits.len()..];
+        if op=='~'
+        {
+            for _ in 0..count
+            {
+                commit=objects.loadCommit(&commit)?.borrow().directParents().into_iter().next().ok_or_else(unknown)?;
+            }
+        }
+        else if count>0
+        {
+            commit=objects.loadCommit(&commit)?.borrow().directParents().into_iter().nth(count-1).ok_or_else(unknown)?;
+        }
+    }
+    Ok(commit)
+}
+
+//gitc rev-parse <rev>，输出commit的完整SHA1
+pub fn gitRevParse(repo:&Repository,rev:&str)->Result<(),GitcError>
+{
+    println!("{}",revParse(repo,rev)?);
+    Ok(())
+}
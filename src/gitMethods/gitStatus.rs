@@ -0,0 +1,243 @@
+use crate::gitUtils::repository::Repository;
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::ignore::IgnoreRules;
+use crate::gitMethods::gitMethods::commitBlobs;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum FileStatus
+{
+    Untracked,
+    //工作区中的文件和暂存区中的版本不同
+    Modified,
+    //暂存的文件已经从工作区删除
+    Deleted,
+    StagedNew,
+    StagedModified,
+    StagedDeleted,
+    //合并冲突还没有解决
+    Unmerged,
+}
+
+impl FileStatus {
+    //porcelain输出中使用的状态字母
+    pub fn code(&self)->char
+    {
+        match self
+        {
+            FileStatus::Untracked=>'?',
+            FileStatus::Modified | FileStatus::StagedModified=>'M',
+            FileStatus::Deleted | FileStatus::StagedDeleted=>'D',
+            FileStatus::StagedNew=>'A',
+            FileStatus::Unmerged=>'U',
+        }
+    }
+    fn label(&self)->&'static str
+    {
+        match self
+        {
+            FileStatus::Untracked=>"",
+            FileStatus::Modified | FileStatus::StagedModified=>"modified:   ",
+            FileStatus::Deleted | FileStatus::StagedDeleted=>"deleted:    ",
+            FileStatus::StagedNew=>"new file:   ",
+            FileStatus::Unmerged=>"unmerged:   ",
+        }
+    }
+}
+
+//一个路径的状态：staged是暂存区相对commit的改动，unstaged是工作区相对暂存区的改动
+#[derive(Debug,Clone,PartialEq)]
+pub struct StatusEntry
+{
+    pub path:String,
+    pub staged:Option<FileStatus>,
+    pub unstaged:Option<FileStatus>,
+}
+
+impl StatusEntry {
+    //和git status --porcelain一样的"XY path"格式
+    pub fn porcelain(&self)->String
+    {
+        if self.unstaged==Some(FileStatus::Untracked)
+        {
+            return format!("?? {}",self.path);
+        }
+        let x=self.staged.map_or(' ',|status| status.code());
+        let y=self.unstaged.map_or(' ',|status| status.code());
+        format!("{}{} {}",x,y,self.path)
+    }
+}
+
+//以root为根目录的工作区中，dir("."或"./xxx")下除.gitc以外的所有文件，返回相对root的"./xxx"形式
+pub fn workingFiles(root:&Path,dir:&str)->Result<Vec<String>,GitcError>
+{
+    let gitDir=root.join(".gitc");
+    let mut files=vec![];
+    for entry in WalkDir::new(root.join(dir)).sort_by_file_name().into_iter().filter_entry(|entry| entry.path()!=gitDir)
+    {
+        let entry=entry.map_err(|err| GitcError::Io(err.into()))?;
+        if entry.file_type().is_file()
+        {
+            let relative=entry.path().strip_prefix(root).unwrap_or(entry.path());
+            files.push(format!("./{}",relative.to_string_lossy().replace('\\',"/")));
+        }
+    }
+    Ok(files)
+}
+
+fn entryOf<'a>(entries:&'a mut BTreeMap<String,StatusEntry>,path:&str)->&'a mut StatusEntry
+{
+    entries.entry(path.to_string()).or_insert_with(|| StatusEntry{path:path.to_string(),staged:None,unstaged:None})
+}
+
+//比较工作区、暂存区和当前分支commitpointer的快照，给出每个有改动的路径的状态
+pub fn statusEntries(repo:&Repository)->Result<Vec<StatusEntry>,GitcError>
+{
+    let Repository{head,objects,..}=repo;
+    let branch=head.currentBranch()?;
+
+    let committed=commitBlobs(objects,branch.borrow().commitpointer.as_deref())?;
+    let staged=head.index.blobs();
+
+    let mut entries:BTreeMap<String,StatusEntry>=BTreeMap::new();
+    for (path,version) in staged.iter()
+    {
+        match committed.get(path)
+        {
+            None=>entryOf(&mut entries,path).staged=Some(FileStatus::StagedNew),
+            Some(old) if old!=version=>entryOf(&mut entries,path).staged=Some(FileStatus::StagedModified),
+            _=>{}
+        }
+    }
+    for path in committed.keys()
+    {
+        if !staged.contains_key(path)
+        {
+            entryOf(&mut entries,path).staged=Some(FileStatus::StagedDeleted);
+        }
+    }
+    //大小和修改时间都没变的文件不需要重新计算SHA1
+    for path in staged.keys()
+    {
+        if !repo.root.join(path).is_file()
+        {
+            entryOf(&mut entries,path).unstaged=Some(FileStatus::Deleted);
+        }
+        else if head.index.isModified(path)?
+        {
+            entryOf(&mut entries,path).unstaged=Some(FileStatus::Modified);
+        }
+    }
+    //被.gitcignore忽略的未暂存文件不显示
+    let rules=IgnoreRules::load(&repo.root)?;
+    for path in workingFiles(&repo.root,".")?
+    {
+        if !staged.contains_key(&path) && !rules.isIgnored(&path,false)
+        {
+            entryOf(&mut entries,&path).unstaged=Some(FileStatus::Untracked);
+        }
+    }
+    //冲突的文件两边都显示为U
+    for path in head.index.unmerged.iter()
+    {
+        let entry=entryOf(&mut entries,path);
+        entry.staged=Some(FileStatus::Unmerged);
+        entry.unstaged=Some(FileStatus::Unmerged);
+    }
+    //已暂存删除但工作区中还在的文件和git一样分成暂存的删除和未跟踪的文件两项
+    let mut res=vec![];
+    for entry in entries.into_values()
+    {
+        if entry.staged.is_some() && entry.unstaged==Some(FileStatus::Untracked)
+        {
+            res.push(StatusEntry{unstaged:None,..entry.clone()});
+            res.push(StatusEntry{staged:None,..entry});
+        }
+        else
+        {
+            res.push(entry);
+        }
+    }
+    Ok(res)
+}
+
+//打印工作区状态，porcelain为true时每行输出一个"XY path"
+pub fn gitStatus(repo:&Repository,porcelain:bool)->Result<(),GitcError>
+{
+    let head=&repo.head;
+    let entries=statusEntries(repo)?;
+    if porcelain
+    {
+        for entry in entries.iter()
+        {
+            println!("{}",entry.porcelain());
+        }
+        return Ok(());
+    }
+
+    println!("On branch {}",head.currentBranchName);
+    let unmerged:Vec<_>=entries.iter().filter(|entry| entry.staged==Some(FileStatus::Unmerged)).map(|entry| &entry.path).collect();
+    if head.mergeHead.is_some() && !unmerged.is_empty()
+    {
+        println!("You have unmerged paths.");
+        println!("  (fix conflicts and run \"gitc commit\")");
+    }
+    else if head.mergeHead.is_some()
+    {
+        println!("All conflicts fixed but you are still merging.");
+        println!("  (use \"gitc commit\" to conclude merge)");
+    }
+    let staged:Vec<_>=entries.iter().filter_map(|entry| entry.staged.filter(|status| *status!=FileStatus::Unmerged).map(|status| (status,&entry.path))).collect();
+    let unstaged:Vec<_>=entries.iter().filter_map(|entry| entry.unstaged.filter(|status| *status!=FileStatus::Untracked && *status!=FileStatus::Unmerged).map(|status| (status,&entry.path))).collect();
+    let untracked:Vec<_>=entries.iter().filter(|entry| entry.unstaged==Some(FileStatus::Untracked)).map(|entry| &entry.path).collect();
+
+    if !staged.is_empty()
+    {
+        println!("Changes to be committed:");
+        for (status,path) in staged.iter()
+        {
+            println!("\t{}{}",status.label(),path);
+        }
+        println!();
+    }
+    if !unmerged.is_empty()
+    {
+        println!("Unmerged paths:");
+        println!("  (use \"gitc add <file>...\" to mark resolution)");
+        for path in unmerged.iter()
+        {
+            println!("\t{}{}",FileStatus::Unmerged.label(),path);
+        }
+        println!();
+    }
+    if !unstaged.is_empty()
+    {
+        println!("Changes not staged for commit:");
+        for (status,path) in unstaged.iter()
+        {
+            println!("\t{}{}",status.label(),path);
+        }
+        println!();
+    }
+    if !untracked.is_empty()
+    {
+        println!("Untracked files:");
+        for path in untracked.iter()
+        {
+            println!("\t{}",path);
+        }
+        println!();
+    }
+    if entries.is_empty()
+    {
+        println!("nothing to commit, working tree clean");
+    }
+    else if staged.is_empty()
+    {
+        println!("no changes added to commit");
+    }
+    Ok(())
+}
@@ -1 +1,10 @@
-pub mod gitMethods;
\ No newline at end of file
+pub mod gitMethods;
+pub mod gitLog;
+pub mod gitStatus;
+pub mod gitMergeBase;
+pub mod gitDiff;
+pub mod gitRepack;
+pub mod gitGc;
+pub mod gitIgnore;
+pub mod gitRevParse;
+pub mod gitConfig;
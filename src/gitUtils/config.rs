@@ -0,0 +1,344 @@
+//分层的配置：用户级的配置文件、仓库的./.gitc/config以及环境变量，后面的覆盖前面的。
+//配置文件和git一样是INI格式，[section]或[section "subsection"]下面每行一个"key = value"，
+//键写成section.key或section.subsection.key，section和key不区分大小写
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::threeWay::ConflictStyle;
+
+use std::env;
+use std::fs;
+use std::path::{Path,PathBuf};
+
+pub const CONFIG_FILE:&str="./.gitc/config";
+
+//set和unset修改哪一个配置文件
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ConfigScope
+{
+    //./.gitc/config
+    Repository,
+    //GITC_CONFIG_GLOBAL指定的文件，默认为~/.gitcconfig
+    User,
+}
+
+impl ConfigScope {
+    //root为工作区的根目录，不在仓库中时为None，此时没有仓库配置
+    pub fn path(&self,root:Option<&Path>)->Option<PathBuf>
+    {
+        match self
+        {
+            ConfigScope::Repository=>root.map(|root| root.join(CONFIG_FILE)),
+            ConfigScope::User=>match env::var_os("GITC_CONFIG_GLOBAL")
+            {
+                Some(path)=>Some(PathBuf::from(path)),
+                None=>env::var_os("HOME").map(|home| Path::new(&home).join(".gitcconfig")),
+            },
+        }
+    }
+}
+
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct Config
+{
+    //按优先级从低到高：用户级配置、仓库配置、环境变量，每层按出现的顺序记录(键,值)，同一个键后出现的生效
+    pub layers:Vec<Vec<(String,String)>>,
+}
+
+//把键拆成(section或section.subsection,key)，section和key转成小写
+fn splitKey(key:&str)->Result<(String,String),GitcError>
+{
+    let invalid=|| GitcError::InvalidConfig(format!("invalid key '{}'",key));
+    let (section,rest)=key.split_once('.').ok_or_else(invalid)?;
+    let (subsection,name)=match rest.rsplit_once('.')
+    {
+        Some((subsection,name))=>(Some(subsection),name),
+        None=>(None,rest),
+    };
+    //section和key只能由字母、数字和-组成，key必须以字母开头
+    let validName=|name:&str| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c=='-');
+    if !validName(section) || !validName(name) || !name.starts_with(|c:char| c.is_ascii_alphabetic())
+    {
+        return Err(invalid());
+    }
+    let section=section.to_ascii_lowercase();
+    let prefix=match subsection
+    {
+        Some(subsection)=>format!("{}.{}",section,subsection),
+        None=>section,
+    };
+    Ok((prefix,name.to_ascii_lowercase()))
+}
+
+pub fn normalizeKey(key:&str)->Result<String,GitcError>
+{
+    let (prefix,name)=splitKey(key)?;
+    Ok(format!("{}.{}",prefix,name))
+}
+
+//"[core]"、"[alias]"、"[branch \"main\"]"这样的section头，返回section或section.subsection
+fn parseSection(line:&str)->Option<String>
+{
+    let inner=line.strip_prefix('[')?.trim_end().strip_suffix(']')?.trim();
+    match inner.split_once(char::is_whitespace)
+    {
+        Some((section,subsection))=>
+        {
+            let subsection=subsection.trim().strip_prefix('"')?.strip_suffix('"')?.replace("\\\"","\"").replace("\\\\","\\");
+            Some(format!("{}.{}",section.to_ascii_lowercase(),subsection))
+        }
+        None=>Some(inner.to_ascii_lowercase()),
+    }
+}
+
+//等号右边的值：双引号内的空白和#;原样保留，引号外的#或;之后是注释，支持\"、\\、\n、\t转义
+fn parseValue(raw:&str)->String
+{
+    let mut res=String::new();
+    let mut keep=0;
+    let mut quoted=false;
+    let mut chars=raw.trim().chars();
+    while let Some(c)=chars.next()
+    {
+        match c
+        {
+            '"'=>
+            {
+                quoted^=true;
+                keep=res.len();
+                continue;
+            }
+            '\\'=>
+            {
+                match chars.next()
+                {
+                    Some('n')=>res.push('\n'),
+                    Some('t')=>res.push('\t'),
+                    Some(other)=>res.push(other),
+                    None=>{}
+                }
+            }
+            '#' | ';' if !quoted=>break,
+            c=>res.push(c),
+        }
+        if quoted || !c.is_whitespace()
+        {
+            keep=res.len();
+        }
+    }
+    res.truncate(keep);
+    res
+}
+
+//写入文件时的值，首尾有空白或含有注释符号、引号时加上双引号
+fn formatValue(value:&str)->String
+{
+    let escaped=value.replace('\\',"\\\\").replace('"',"\\\"").replace('\n',"\\n").replace('\t',"\\t");
+    if value.trim()!=value || value.contains(['#',';']) || escaped!=value
+    {
+        format!("\"{}\"",escaped)
+    }
+    else {
+        escaped
+    }
+}
+
+fn sectionHeader(prefix:&str)->String
+{
+    match prefix.split_once('.')
+    {
+        Some((section,subsection))=>format!("[{} \"{}\"]",section,subsection.replace('\\',"\\\\").replace('"',"\\\"")),
+        None=>format!("[{}]",prefix),
+    }
+}
+
+//配置文件中的一行：所在的section，以及这一行设置的(键,值)，不是键值的行为None
+struct ConfigLine
+{
+    section:String,
+    entry:Option<(String,String)>,
+}
+
+fn parseLines(text:&str)->Result<Vec<ConfigLine>,GitcError>
+{
+    let mut res=vec![];
+    let mut section=String::new();
+    for (number,line) in text.lines().enumerate()
+    {
+        let trimmed=line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';')
+        {
+            res.push(ConfigLine{section:section.clone(),entry:None});
+            continue;
+        }
+        if trimmed.starts_with('[')
+        {
+            section=parseSection(trimmed).ok_or_else(|| GitcError::InvalidConfig(format!("bad section header at line {}",number+1)))?;
+            res.push(ConfigLine{section:section.clone(),entry:None});
+            continue;
+        }
+        if section.is_empty()
+        {
+            return Err(GitcError::InvalidConfig(format!("key outside of any section at line {}",number+1)));
+        }
+        //只写键名时表示true
+        let (name,value)=match trimmed.split_once('=')
+        {
+            Some((name,value))=>(name.trim(),parseValue(value)),
+            None=>(trimmed,String::from("true")),
+        };
+        let key=normalizeKey(&format!("{}.{}",section,name)).map_err(|_| GitcError::InvalidConfig(format!("bad key '{}' at line {}",name,number+1)))?;
+        res.push(ConfigLine{section:section.clone(),entry:Some((key,value))});
+    }
+    Ok(res)
+}
+
+//读出一个配置文件中的所有键值，文件不存在时为空
+pub fn readConfigFile(path:&Path)->Result<Vec<(String,String)>,GitcError>
+{
+    if !path.is_file()
+    {
+        return Ok(vec![]);
+    }
+    Ok(parseLines(&fs::read_to_string(path)?)?.into_iter().filter_map(|line| line.entry).collect())
+}
+
+//在配置文件中设置(Some)或删除(None)一个键，其余的行和注释保持不变，返回文件是否被修改。
+//已有这个键时改写最后一次出现的位置，否则加到它所在section的末尾，没有这个section时在文件末尾新建
+pub fn writeConfigFile(path:&Path,key:&str,value:Option<&str>)->Result<bool,GitcError>
+{
+    let (prefix,name)=splitKey(key)?;
+    let key=format!("{}.{}",prefix,name);
+    let text=if path.is_file() {fs::read_to_string(path)?} else {String::new()};
+    let parsed=parseLines(&text)?;
+    let mut lines:Vec<String>=text.lines().map(String::from).collect();
+    let isKey=|line:&ConfigLine| line.entry.as_ref().is_some_and(|(found,_)| *found==key);
+    let existing=parsed.iter().rposition(isKey);
+    match (existing,value)
+    {
+        (Some(i),Some(value))=>lines[i]=format!("\t{} = {}",name,formatValue(value)),
+        (Some(_),None)=>
+        {
+            lines=lines.into_iter().zip(parsed.iter()).filter(|(_,line)| !isKey(line)).map(|(text,_)| text).collect();
+        }
+        (None,Some(value))=>
+        {
+            let line=format!("\t{} = {}",name,formatValue(value));
+            let inSection=|i:usize| parsed[i].section==prefix && (parsed[i].entry.is_some() || lines[i].trim_start().starts_with('['));
+            match (0..lines.len()).rposition(inSection)
+            {
+                Some(i)=>lines.insert(i+1,line),
+                None=>
+                {
+                    lines.push(sectionHeader(&prefix));
+                    lines.push(line);
+                }
+            }
+        }
+        (None,None)=>return Ok(false),
+    }
+    if let Some(dir)=path.parent().filter(|dir| !dir.as_os_str().is_empty())
+    {
+        fs::create_dir_all(dir)?;
+    }
+    let temp=path.with_extension("tmp");
+    let mut contents=lines.join("\n");
+    contents.push('\n');
+    fs::write(&temp,contents)?;
+    fs::rename(&temp,path)?;
+    Ok(true)
+}
+
+impl Config {
+    //依次读出用户级配置、root下的./.gitc/config，再加上环境变量GITC_CONFIG_COUNT、GITC_CONFIG_KEY_<n>、GITC_CONFIG_VALUE_<n>给出的键值。
+    //root为None时没有仓库配置
+    pub fn load(root:Option<&Path>)->Result<Self,GitcError>
+    {
+        let mut layers=vec![];
+        for scope in [ConfigScope::User,ConfigScope::Repository]
+        {
+            layers.push(match scope.path(root)
+            {
+                Some(path)=>readConfigFile(&path)?,
+                None=>vec![],
+            });
+        }
+        let mut overrides=vec![];
+        if let Ok(count)=env::var("GITC_CONFIG_COUNT")
+        {
+            let count:usize=count.parse().map_err(|_| GitcError::InvalidConfig(format!("bad GITC_CONFIG_COUNT '{}'",count)))?;
+            for i in 0..count
+            {
+                let key=env::var(format!("GITC_CONFIG_KEY_{}",i)).map_err(|_| GitcError::InvalidConfig(format!("missing GITC_CONFIG_KEY_{}",i)))?;
+                let value=env::var(format!("GITC_CONFIG_VALUE_{}",i)).unwrap_or_default();
+                overrides.push((normalizeKey(&key)?,value));
+            }
+        }
+        layers.push(overrides);
+        Ok(Config{layers})
+    }
+
+    pub fn get(&self,key:&str)->Option<String>
+    {
+        let key=normalizeKey(key).ok()?;
+        self.layers.iter().rev().flat_map(|layer| layer.iter().rev()).find(|(found,_)| *found==key).map(|(_,value)| value.clone())
+    }
+
+    //所有层的键值，按优先级从低到高
+    pub fn list(&self)->Vec<(String,String)>
+    {
+        self.layers.iter().flatten().cloned().collect()
+    }
+
+    //true/yes/on/1或false/no/off/0
+    pub fn getBool(&self,key:&str)->Result<Option<bool>,GitcError>
+    {
+        self.get(key).map(|value| match value.to_ascii_lowercase().as_str()
+        {
+            "true" | "yes" | "on" | "1"=>Ok(true),
+            "false" | "no" | "off" | "0" | ""=>Ok(false),
+            _=>Err(GitcError::InvalidConfig(format!("bad boolean value '{}' for '{}'",value,key))),
+        }).transpose()
+    }
+
+    pub fn getInt(&self,key:&str)->Result<Option<i64>,GitcError>
+    {
+        self.get(key).map(|value| value.parse().map_err(|_| GitcError::InvalidConfig(format!("bad numeric value '{}' for '{}'",value,key)))).transpose()
+    }
+
+    //user.name和user.email组成的"名字 <邮箱>"，没有设置user.name时为None
+    pub fn identity(&self)->Option<String>
+    {
+        let name=self.get("user.name")?;
+        Some(match self.get("user.email")
+        {
+            Some(email)=>format!("{} <{}>",name,email),
+            None=>name,
+        })
+    }
+
+    //core.compression：zlib的压缩级别0到9，-1或没有设置时用默认级别
+    pub fn compression(&self)->Result<Option<u32>,GitcError>
+    {
+        match self.getInt("core.compression")?
+        {
+            None | Some(-1)=>Ok(None),
+            Some(level @ 0..=9)=>Ok(Some(level as u32)),
+            Some(level)=>Err(GitcError::InvalidConfig(format!("bad zlib compression level {}",level))),
+        }
+    }
+
+    //merge.conflictstyle：merge或diff3
+    pub fn conflictStyle(&self)->Result<ConflictStyle,GitcError>
+    {
+        match self.get("merge.conflictstyle")
+        {
+            Some(name)=>ConflictStyle::fromName(&name).ok_or_else(|| GitcError::InvalidConfig(format!("unknown conflict style '{}'",name))),
+            None=>Ok(ConflictStyle::Merge),
+        }
+    }
+
+    //alias.<name>展开后的命令行参数
+    pub fn alias(&self,name:&str)->Option<Vec<String>>
+    {
+        self.get(&format!("alias.{}",name)).map(|value| value.split_whitespace().map(String::from).collect())
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+use std::io;
+
+//gitMethods中所有操作统一返回的错误类型，调用者可以根据不同的variant做出处理
+#[derive(Debug)]
+pub enum GitcError
+{
+    BranchExists(String),
+    UnknownBranch(String),
+    ObjectNotFound(String),
+    UnknownRevision(String),
+    //缩写的SHA1对应多个commit
+    AmbiguousRevision(String),
+    PathNotFound(String),
+    NotARepository,
+    MergeInProgress,
+    //还没有任何提交时暂存区为空
+    NothingToCommit,
+    NonFastForward(String),
+    //第一个字段是会改写这些文件的操作(checkout/merge)
+    WouldOverwrite(String,Vec<String>),
+    //没有-r时不能删除整个文件夹
+    RecursiveRequired(String),
+    //rm会丢掉还没提交的改动，需要-f
+    LocalModifications(Vec<String>),
+    //路径被.gitcignore中的规则忽略
+    PathIgnored(String),
+    //GITC_AUTHOR_DATE、--since这类无法解析的时间
+    InvalidDate(String),
+    //配置文件的格式、键名或值不正确
+    InvalidConfig(String),
+    //要读取或删除的配置项没有设置
+    UnknownConfigKey(String),
+    //命令行中的路径在工作区之外
+    OutsideRepository(String),
+    Io(io::Error),
+    CorruptStore(String),
+}
+
+impl fmt::Display for GitcError
+{
+    fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result
+    {
+        match self
+        {
+            GitcError::BranchExists(name)=>write!(f,"a branch named '{}' already exists",name),
+            GitcError::UnknownBranch(name)=>write!(f,"branch '{}' not found",name),
+            GitcError::ObjectNotFound(name)=>write!(f,"object '{}' not found",name),
+            GitcError::UnknownRevision(name)=>write!(f,"unknown revision '{}'",name),
+            GitcError::AmbiguousRevision(name)=>write!(f,"short SHA1 {} is ambiguous",name),
+            GitcError::PathNotFound(path)=>write!(f,"pathspec '{}' did not match any files",path),
+            GitcError::NotARepository=>write!(f,"not a gitc repository (run 'gitc init' first)"),
+            GitcError::MergeInProgress=>write!(f,"a merge is in progress, resolve the conflicts and commit first"),
+            GitcError::NothingToCommit=>write!(f,"nothing to commit (use 'gitc add' to track files)"),
+            GitcError::NonFastForward(name)=>write!(f,"not possible to fast-forward to '{}', aborting",name),
+            GitcError::WouldOverwrite(operation,paths)=>write!(f,"your local changes to the following files would be overwritten by {}: {}",operation,paths.join(", ")),
+            GitcError::RecursiveRequired(path)=>write!(f,"not removing '{}' recursively without -r",path),
+            GitcError::LocalModifications(paths)=>write!(f,"the following files have changes that would be lost (use --cached to keep the file, or -f to force removal): {}",paths.join(", ")),
+            GitcError::PathIgnored(path)=>write!(f,"the path '{}' is ignored by one of your .gitcignore files",path),
+            GitcError::InvalidDate(date)=>write!(f,"invalid date format: {}",date),
+            GitcError::InvalidConfig(reason)=>write!(f,"bad config: {}",reason),
+            GitcError::UnknownConfigKey(key)=>write!(f,"config key '{}' is not set",key),
+            GitcError::OutsideRepository(path)=>write!(f,"'{}' is outside repository",path),
+            GitcError::Io(err)=>write!(f,"{}",err),
+            GitcError::CorruptStore(reason)=>write!(f,"corrupt object store: {}",reason),
+        }
+    }
+}
+
+impl std::error::Error for GitcError
+{
+    fn source(&self)->Option<&(dyn std::error::Error+'static)>
+    {
+        match self
+        {
+            GitcError::Io(err)=>Some(err),
+            _=>None,
+        }
+    }
+}
+
+impl From<io::Error> for GitcError
+{
+    fn from(err:io::Error)->Self
+    {
+        GitcError::Io(err)
+    }
+}
+
+impl From<bincode::Error> for GitcError
+{
+    fn from(err:bincode::Error)->Self
+    {
+        GitcError::CorruptStore(err.to_string())
+    }
+}
@@ -1,13 +1,17 @@
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::index::Index;
+use crate::gitUtils::signature::Signature;
+use crate::gitUtils::config::Config;
+use crate::gitUtils::pack::PackIndex;
+
 use serde::{Serialize, Deserialize};
-use serde::Serializer;
-use bincode::{serialize, deserialize};
-use std::io::{Bytes, Read, Write};
+use std::io;
+use std::io::Read;
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::collections::BTreeMap;
 use std::fs;
-use std::fs::File;
+use std::path::{Path,PathBuf};
 
 
 #[repr(C)]
@@ -15,7 +19,8 @@ use std::fs::File;
 pub struct Branch
 {
     pub name:String,
-    pub commitpointer:Option<Rc<RefCell<Commit>>>,
+    //分支最新的commit的SHA1，还没有提交过时为None
+    pub commitpointer:Option<String>,
     pub references:Rc<RefCell<References>>,
 }
 
@@ -23,8 +28,8 @@ impl Branch {
     pub fn new(name:String)->Self
     {
         Branch{
-            name:name,
-            commitpointer:Some(Rc::new(RefCell::new(Commit::new()))),
+            name,
+            commitpointer:None,
             references:Rc::new(RefCell::new(References::new()))
         }
     }
@@ -37,6 +42,21 @@ pub struct HEAD
 {
     pub currentBranchName:String,
     pub branch:BTreeMap<String,Rc<RefCell<Branch>>>,
+    //合并出现冲突时记录被合并进来的commit，解决冲突后的下一次提交会把它作为mergeparent
+    pub mergeHead:Option<String>,
+    //暂存区，所有分支共用，单独保存在./.gitc/index中
+    #[serde(skip)]
+    pub index:Index,
+    //用户级配置、./.gitc/config和环境变量合并后的配置，随head一起读出，不随head保存
+    #[serde(skip)]
+    pub config:Config,
+}
+
+impl Default for HEAD {
+    fn default()->Self
+    {
+        Self::new()
+    }
 }
 
 impl HEAD {
@@ -46,40 +66,45 @@ impl HEAD {
         {
             currentBranchName:String::from(""),
             branch:BTreeMap::new(),
+            mergeHead:None,
+            index:Index::new(),
+            config:Config::default(),
         }
     }
-    pub fn addbranch(&mut self,branchname:String)
+    pub fn currentBranch(&self)->Result<Rc<RefCell<Branch>>,GitcError>
+    {
+        self.branch.get(&self.currentBranchName).cloned().ok_or_else(|| GitcError::UnknownBranch(self.currentBranchName.clone()))
+    }
+    pub fn addbranch(&mut self,branchname:String)->Result<(),GitcError>
     {
-        if self.branch.contains_key(&branchname)==false
+        if self.branch.contains_key(&branchname)
         {
-            let mut newbranch=Branch::new(branchname.clone());
-
-            for (key,value) in self.branch.get(&self.currentBranchName).cloned().unwrap().borrow().references.borrow().refermap.clone()
-            {
-                newbranch.references.borrow_mut().refermap.insert(key,value);
-            }
-            newbranch.commitpointer=self.branch.get(&self.currentBranchName).cloned().unwrap().borrow().commitpointer.clone();
-            self.branch.insert(branchname.clone(),Rc::new(RefCell::new(newbranch)));
-        }
-        else {
-            println!("This name is contained.Try another name");
+            return Err(GitcError::BranchExists(branchname));
         }
+        let current=self.currentBranch()?;
+        let mut newbranch=Branch::new(branchname.clone());
 
+        for (key,value) in current.borrow().references.borrow().refermap.clone()
+        {
+            newbranch.references.borrow_mut().refermap.insert(key,value);
+        }
+        newbranch.commitpointer=current.borrow().commitpointer.clone();
+        self.branch.insert(branchname,Rc::new(RefCell::new(newbranch)));
+        Ok(())
     }
-    pub fn checkout(&mut self,branchname:String)
+    pub fn checkout(&mut self,branchname:String)->Result<(),GitcError>
     {
-        if self.branch.contains_key(&branchname)
+        if !self.branch.contains_key(&branchname)
         {
-            self.currentBranchName=branchname;
-        }
-        else {
-            println!("uncontained name!");
+            return Err(GitcError::UnknownBranch(branchname));
         }
+        self.currentBranchName=branchname;
+        Ok(())
     }
 
     pub fn showAllBranch(&self)
     {
-        for (key,value) in self.branch.iter()
+        for key in self.branch.keys()
         {
             println!("{:?}",key);
         }
@@ -104,6 +129,13 @@ pub struct References
     pub refermap:BTreeMap<String,String>,
 }
 
+impl Default for References {
+    fn default()->Self
+    {
+        Self::new()
+    }
+}
+
 impl References {
     pub fn new()->Self
     {
@@ -116,10 +148,9 @@ impl References {
     {
         self.refermap.insert(name,id);
     }
-    pub fn read_reference(&self,name:String)->String
+    pub fn read_reference(&self,name:String)->Result<String,GitcError>
     {
-        let id=String::from(self.refermap.get(&name).unwrap());
-        return id;
+        self.refermap.get(&name).cloned().ok_or(GitcError::ObjectNotFound(name))
     }
 }
 #[repr(C)]
@@ -129,6 +160,22 @@ pub struct Objects
     pub treemap:BTreeMap<String,Rc<RefCell<tree>>>,
     pub commitmap:BTreeMap<String,Rc<RefCell<Commit>>>,
     pub blobmap:BTreeMap<String,Rc<RefCell<blob>>>,
+    //写入对象时zlib的压缩级别(core.compression)，None为默认级别
+    #[serde(skip)]
+    pub compression:Option<u32>,
+    //工作区的根目录，对象按root下的./.gitc/objects读写。为空时相对当前目录
+    #[serde(skip)]
+    pub root:PathBuf,
+    //读过的pack的index，见Objects::packs
+    #[serde(skip)]
+    pub packCache:RefCell<Option<Rc<Vec<PackIndex>>>>,
+}
+
+impl Default for Objects {
+    fn default()->Self
+    {
+        Self::new()
+    }
 }
 
 impl Objects {
@@ -139,22 +186,42 @@ impl Objects {
             treemap:BTreeMap::new(),
             commitmap:BTreeMap::new(),
             blobmap:BTreeMap::new(),
+            compression:None,
+            root:PathBuf::new(),
+            packCache:RefCell::new(None),
         }
     }
 
+    //读写root下./.gitc/objects的空缓存
+    pub fn at(root:&Path)->Self
+    {
+        let mut objects=Objects::new();
+        objects.root=root.to_path_buf();
+        objects
+    }
+
 }
 #[repr(C)]
 #[derive(Debug,Serialize,Deserialize,PartialEq)]
 pub struct Commit
 {
-    pub parents:Vec<Option<Rc<RefCell<Commit>>>>,
-    pub mergeparents:Vec<Option<Rc<RefCell<Commit>>>>,
-    pub author:String ,
+    pub parents:Vec<String>,
+    pub mergeparents:Vec<String>,
+    pub author:Signature,
+    pub committer:Signature,
     pub message:String,
-    pub snapshot:Rc<RefCell<tree>>,
+    //快照根tree的SHA1
+    pub snapshot:String,
     pub t:objecttype,
 }
 
+impl Default for Commit {
+    fn default()->Self
+    {
+        Self::new()
+    }
+}
+
 impl Commit {
     pub fn new()->Self
     {
@@ -162,21 +229,30 @@ impl Commit {
         {
             parents:Vec::new(),
             mergeparents:Vec::new(),
-            author:String::new(),
+            author:Signature::default(),
+            committer:Signature::default(),
             message:String::new(),
-            snapshot:Rc::new(RefCell::new(tree::new(String::from(".")))),
+            snapshot:String::new(),
             t:objecttype::commit,
 
         }
     }
+    //parents中是第一个父commit，mergeparents中按顺序是被合并进来的commit，更早的祖先沿着它们在objects中查找
+    pub fn directParents(&self)->Vec<String>
+    {
+        self.parents.iter().chain(self.mergeparents.iter()).cloned().collect()
+    }
 }
 #[repr(C)]
 #[derive(Debug,Serialize,Deserialize,PartialEq)]
 pub struct tree
 {
     pub name:String,
-    pub trees:BTreeMap<String,Rc<RefCell<tree>>>,
-    pub blobs:BTreeMap<String,Rc<RefCell<blob>>>,
+    //子文件夹和文件的完整路径到它们SHA1的映射
+    pub trees:BTreeMap<String,String>,
+    pub blobs:BTreeMap<String,String>,
+    //mode不是100644的文件(比如可执行文件100755)的完整路径到mode的映射
+    pub modes:BTreeMap<String,u32>,
     pub t:objecttype,
 }
 
@@ -184,9 +260,10 @@ impl tree {
     pub fn new(name:String)->Self
     {
         tree
-        {   name:String::from(name),
+        {   name,
             trees:BTreeMap::new(),
             blobs:BTreeMap::new(),
+            modes:BTreeMap::new(),
             t:objecttype::tree,
         }
     }
@@ -205,15 +282,16 @@ impl  blob {
     {
         blob
         {
-            name:String::from(name),
+            name,
             contents:vec![],
             t:objecttype::blob,
         }
     }
-    pub fn getContents(&mut self,path:String)
+    pub fn getContents(&mut self,path:String)->io::Result<()>
     {
-        let mut file_open = fs::File::open(path).unwrap();
-        file_open.read_to_end(&mut self.contents);
+        let mut file_open = fs::File::open(path)?;
+        file_open.read_to_end(&mut self.contents)?;
+        Ok(())
     }
 
 }
\ No newline at end of file
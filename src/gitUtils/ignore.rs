@@ -0,0 +1,160 @@
+//.gitcignore和./.gitc/info/exclude中的忽略规则，语法和.gitignore相同：
+//"#"开头的行是注释，"!"表示重新包含，"/"结尾只匹配文件夹，含有"/"的规则相对规则文件所在的文件夹匹配，否则只匹配文件名
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::wildmatch::wildmatch;
+
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+pub const IGNORE_FILE:&str=".gitcignore";
+pub const EXCLUDE_FILE:&str="./.gitc/info/exclude";
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct IgnoreRule
+{
+    //规则所在的文件以及行号，行号从1开始
+    pub source:String,
+    pub line:usize,
+    //规则文件中的原文
+    pub text:String,
+    pub negated:bool,
+    base:String,
+    pattern:String,
+    dirOnly:bool,
+    anchored:bool,
+}
+
+impl IgnoreRule {
+    //解析规则文件中的一行，空行和注释返回None。base为规则生效的文件夹，如"."或"./demo"
+    pub fn parse(line:&str,base:&str,source:&str,number:usize)->Option<Self>
+    {
+        let text=line.trim_end_matches(['\r','\n']);
+        //行尾的空格会被忽略，除非用"\ "转义
+        let mut pattern=text.trim_end_matches(' ').to_string();
+        if pattern.ends_with('\\') && text.len()>pattern.len()
+        {
+            pattern.push(' ');
+        }
+        if pattern.is_empty() || pattern.starts_with('#')
+        {
+            return None;
+        }
+        let negated=pattern.starts_with('!');
+        //"!"表示重新包含，"\!"和"\#"是以这两个字符开头的普通规则
+        if negated || pattern.starts_with("\\!") || pattern.starts_with("\\#")
+        {
+            pattern.remove(0);
+        }
+        let dirOnly=pattern.ends_with('/');
+        let pattern=pattern.trim_end_matches('/');
+        let anchored=pattern.contains('/');
+        let pattern=pattern.trim_start_matches('/').to_string();
+        if pattern.is_empty()
+        {
+            return None;
+        }
+        Some(IgnoreRule{source:source.to_string(),line:number,text:text.to_string(),negated,base:base.to_string(),pattern,dirOnly,anchored})
+    }
+
+    //path为"./a/b"形式的路径
+    pub fn matches(&self,path:&str,isDir:bool)->bool
+    {
+        if self.dirOnly && !isDir
+        {
+            return false;
+        }
+        let relative=if self.base=="." {path.strip_prefix("./")} else {path.strip_prefix(&format!("{}/",self.base))};
+        let Some(relative)=relative else {return false};
+        if self.anchored
+        {
+            wildmatch(&self.pattern,relative,true)
+        }
+        else
+        {
+            wildmatch(&self.pattern,relative.rsplit('/').next().unwrap_or(relative),true)
+        }
+    }
+}
+
+//按优先级从低到高排列的所有规则：./.gitc/info/exclude，然后是各层的.gitcignore，越深的文件夹优先级越高
+#[derive(Debug,Clone,Default)]
+pub struct IgnoreRules
+{
+    pub rules:Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    pub fn new()->Self
+    {
+        IgnoreRules{rules:vec![]}
+    }
+
+    //path为相对root的"./xxx"形式
+    fn addFile(&mut self,root:&Path,path:&str,base:&str)->Result<(),GitcError>
+    {
+        let file=root.join(path);
+        if !file.is_file()
+        {
+            return Ok(());
+        }
+        let source=path.trim_start_matches("./");
+        for (i,line) in fs::read_to_string(file)?.lines().enumerate()
+        {
+            self.rules.extend(IgnoreRule::parse(line,base,source,i+1));
+        }
+        Ok(())
+    }
+
+    //读出以root为根目录的工作区中所有的规则
+    pub fn load(root:&Path)->Result<Self,GitcError>
+    {
+        let mut res=IgnoreRules::new();
+        res.addFile(root,EXCLUDE_FILE,".")?;
+        let gitDir=root.join(".gitc");
+        let mut files=vec![];
+        for entry in WalkDir::new(root).into_iter().filter_entry(|entry| entry.path()!=gitDir)
+        {
+            let entry=entry.map_err(|err| GitcError::Io(err.into()))?;
+            if entry.file_type().is_file() && entry.file_name()==IGNORE_FILE
+            {
+                let relative=entry.path().strip_prefix(root).unwrap_or(entry.path());
+                files.push(format!("./{}",relative.to_string_lossy().replace('\\',"/")));
+            }
+        }
+        files.sort_by_key(|file| (file.matches('/').count(),file.clone()));
+        for file in files
+        {
+            let base=file[..file.len()-IGNORE_FILE.len()-1].to_string();
+            res.addFile(root,&file,&base)?;
+        }
+        Ok(res)
+    }
+
+    //最后一条匹配path本身的规则
+    fn lastMatch(&self,path:&str,isDir:bool)->Option<&IgnoreRule>
+    {
+        self.rules.iter().rev().find(|rule| rule.matches(path,isDir))
+    }
+
+    //决定path是否被忽略的规则(可能是"!"规则)。
+    //和git一样，文件夹被忽略之后其中的文件不能再被"!"重新包含
+    pub fn matchOf(&self,path:&str,isDir:bool)->Option<&IgnoreRule>
+    {
+        let parts:Vec<&str>=path.split('/').collect();
+        for i in 2..parts.len()
+        {
+            let dir=parts[..i].join("/");
+            if let Some(rule)=self.lastMatch(&dir,true).filter(|rule| !rule.negated)
+            {
+                return Some(rule);
+            }
+        }
+        self.lastMatch(path,isDir)
+    }
+
+    pub fn isIgnored(&self,path:&str,isDir:bool)->bool
+    {
+        self.matchOf(path,isDir).is_some_and(|rule| !rule.negated)
+    }
+}
@@ -0,0 +1,192 @@
+//暂存区./.gitc/index：每个暂存的文件记录blob的SHA1以及暂存时的mode、大小和修改时间，
+//工作区文件的这些信息没有变化时不需要重新计算SHA1
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::objectStore::hashBlob;
+
+use std::collections::{BTreeMap,BTreeSet};
+use std::fs;
+use std::path::{Path,PathBuf};
+use std::time::UNIX_EPOCH;
+use bincode::{serialize,deserialize};
+use serde::{Serialize,Deserialize};
+
+pub const INDEX_FILE:&str="./.gitc/index";
+pub const UNMERGED_FILE:&str="./.gitc/unmerged";
+pub const MODE_FILE:u32=0o100644;
+pub const MODE_EXECUTABLE:u32=0o100755;
+
+#[derive(Debug,Clone,PartialEq,Eq,Serialize,Deserialize)]
+pub struct IndexEntry
+{
+    pub path:String,
+    pub id:String,
+    pub mode:u32,
+    pub size:u64,
+    //修改时间，单位为纳秒，0表示没有记录，下次比较时一定会重新计算SHA1
+    pub mtime:u128,
+}
+
+//文件的mode、大小和修改时间
+fn statOf(metadata:&fs::Metadata)->(u32,u64,u128)
+{
+    #[cfg(unix)]
+    let executable={
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode()&0o111!=0
+    };
+    #[cfg(not(unix))]
+    let executable=false;
+    let mtime=metadata.modified().ok().and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map_or(0,|time| time.as_nanos());
+    (if executable {MODE_EXECUTABLE} else {MODE_FILE},metadata.len(),mtime)
+}
+
+//工作区中的文件是否是这个mode。不支持可执行位的系统上无法判断，和git的core.filemode=false一样认为相同
+pub fn sameMode(metadata:&fs::Metadata,mode:u32)->bool
+{
+    !cfg!(unix) || statOf(metadata).0==mode
+}
+
+#[derive(Debug,Clone,Default,Serialize,Deserialize)]
+pub struct Index
+{
+    pub entries:BTreeMap<String,IndexEntry>,
+    //合并冲突还没有解决的文件，重新暂存或删除后才算解决。单独保存在./.gitc/unmerged中，每行一个路径
+    #[serde(skip)]
+    pub unmerged:BTreeSet<String>,
+    //index文件写入的时间。和它同时或更晚修改的文件即使大小和修改时间都没变也可能改动过，需要重新计算SHA1
+    #[serde(skip)]
+    savedAt:Option<u128>,
+    //工作区的根目录，index文件和暂存的"./xxx"路径都相对它。为空时相对当前目录
+    #[serde(skip)]
+    pub root:PathBuf,
+}
+
+impl PartialEq for Index {
+    fn eq(&self,other:&Self)->bool
+    {
+        self.entries==other.entries && self.unmerged==other.unmerged
+    }
+}
+
+impl Index {
+    pub fn new()->Self
+    {
+        Index{entries:BTreeMap::new(),unmerged:BTreeSet::new(),savedAt:None,root:PathBuf::new()}
+    }
+
+    //读出root下的./.gitc/index和./.gitc/unmerged，不存在时为空
+    pub fn load(root:&Path)->Result<Self,GitcError>
+    {
+        let indexFile=root.join(INDEX_FILE);
+        let unmergedFile=root.join(UNMERGED_FILE);
+        let mut index=if indexFile.is_file()
+        {
+            let mut index:Index=deserialize(&fs::read(&indexFile)?)?;
+            index.savedAt=Some(statOf(&fs::metadata(&indexFile)?).2);
+            index
+        }
+        else {
+            Index::new()
+        };
+        if unmergedFile.is_file()
+        {
+            index.unmerged=fs::read_to_string(&unmergedFile)?.lines().map(String::from).collect();
+        }
+        index.root=root.to_path_buf();
+        Ok(index)
+    }
+
+    pub fn save(&mut self)->Result<(),GitcError>
+    {
+        let indexFile=self.root.join(INDEX_FILE);
+        let unmergedFile=self.root.join(UNMERGED_FILE);
+        let temp=indexFile.with_extension("tmp");
+        fs::write(&temp,serialize(&self)?)?;
+        fs::rename(&temp,&indexFile)?;
+        self.savedAt=Some(statOf(&fs::metadata(&indexFile)?).2);
+        if !self.unmerged.is_empty()
+        {
+            let lines:Vec<&str>=self.unmerged.iter().map(String::as_str).collect();
+            fs::write(&unmergedFile,lines.join("\n")+"\n")?;
+        }
+        else if unmergedFile.is_file()
+        {
+            fs::remove_file(&unmergedFile)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self,path:&str)->Option<&IndexEntry>
+    {
+        self.entries.get(path)
+    }
+
+    pub fn contains(&self,path:&str)->bool
+    {
+        self.entries.contains_key(path)
+    }
+
+    //暂存path的id，metadata为None时不记录文件信息。重新暂存的文件的合并冲突算作已经解决
+    pub fn stage(&mut self,path:&str,id:&str,metadata:Option<&fs::Metadata>)
+    {
+        self.unmerged.remove(path);
+        let (mode,size,mtime)=metadata.map_or((MODE_FILE,0,0),statOf);
+        self.entries.insert(path.to_string(),IndexEntry{path:path.to_string(),id:id.to_string(),mode,size,mtime});
+    }
+
+    //按tree中的mode暂存path的id，工作区中的文件正好是这个版本时同时记录它的文件信息
+    pub fn record(&mut self,path:&str,id:&str,mode:u32)->Result<(),GitcError>
+    {
+        let file=self.root.join(path);
+        let metadata=match fs::metadata(&file)
+        {
+            Ok(metadata) if metadata.is_file() && sameMode(&metadata,mode) && hashBlob(&fs::read(&file)?)==id=>Some(metadata),
+            _=>None,
+        };
+        self.stage(path,id,metadata.as_ref());
+        if let Some(entry)=self.entries.get_mut(path)
+        {
+            entry.mode=mode;
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self,path:&str)->bool
+    {
+        self.unmerged.remove(path);
+        self.entries.remove(path).is_some()
+    }
+
+    //工作区中的文件和暂存时的mode、大小、修改时间都相同，可以认为内容没有变化
+    pub fn isClean(&self,path:&str,metadata:&fs::Metadata)->bool
+    {
+        let Some(entry)=self.entries.get(path) else {return false};
+        let (_,size,mtime)=statOf(metadata);
+        entry.mtime!=0 && sameMode(metadata,entry.mode) && entry.size==size && entry.mtime==mtime && self.savedAt.is_some_and(|saved| mtime<saved)
+    }
+
+    //工作区中path的内容或mode是否和暂存的版本不同，文件信息没变时不读文件
+    pub fn isModified(&self,path:&str)->Result<bool,GitcError>
+    {
+        let Some(entry)=self.entries.get(path) else {return Ok(true)};
+        let file=self.root.join(path);
+        let metadata=fs::metadata(&file)?;
+        if self.isClean(path,&metadata)
+        {
+            return Ok(false);
+        }
+        Ok(!sameMode(&metadata,entry.mode) || hashBlob(&fs::read(&file)?)!=entry.id)
+    }
+
+    //所有暂存的文件，路径到blob的SHA1
+    pub fn files(&self)->BTreeMap<String,String>
+    {
+        self.entries.iter().map(|(path,entry)| (path.clone(),entry.id.clone())).collect()
+    }
+
+    //所有暂存的文件，路径到blob的SHA1和暂存时的mode
+    pub fn blobs(&self)->BTreeMap<String,(String,u32)>
+    {
+        self.entries.iter().map(|(path,entry)| (path.clone(),(entry.id.clone(),entry.mode))).collect()
+    }
+}
@@ -0,0 +1,194 @@
+//存储版本7之前的对象模型：分支、commit和tree直接嵌套保存它们指向的commit、父commit、子tree和blob，
+//bincode按值序列化，每个commit都带着祖先的完整副本。这里只用来读出旧版本的./.gitc/maps/head和./.gitc/maps/objs，
+//把其中的对象写入./.gitc/objects并换成按SHA1引用
+use crate::gitUtils::gitUtils::{HEAD,Branch,References,Objects,Commit,tree,blob,objecttype};
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::signature::Signature;
+
+use serde::{Serialize,Deserialize};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+#[repr(C)]
+#[derive(Debug,Serialize,Deserialize)]
+pub struct LegacyHEAD
+{
+    pub currentBranchName:String,
+    pub branch:BTreeMap<String,Rc<RefCell<LegacyBranch>>>,
+    pub mergeHead:Option<String>,
+}
+
+#[repr(C)]
+#[derive(Debug,Serialize,Deserialize)]
+pub struct LegacyBranch
+{
+    pub name:String,
+    //还没有提交过的分支指向一个提交信息为空的占位commit
+    pub commitpointer:Option<Rc<RefCell<LegacyCommit>>>,
+    pub references:Rc<RefCell<References>>,
+}
+
+#[repr(C)]
+#[derive(Debug,Serialize,Deserialize)]
+pub struct LegacyObjects
+{
+    pub treemap:BTreeMap<String,Rc<RefCell<LegacyTree>>>,
+    pub commitmap:BTreeMap<String,Rc<RefCell<LegacyCommit>>>,
+    pub blobmap:BTreeMap<String,Rc<RefCell<blob>>>,
+}
+
+#[repr(C)]
+#[derive(Debug,Serialize,Deserialize)]
+pub struct LegacyCommit
+{
+    pub parents:Vec<Option<Rc<RefCell<LegacyCommit>>>>,
+    pub mergeparents:Vec<Option<Rc<RefCell<LegacyCommit>>>>,
+    pub author:String,
+    pub message:String,
+    pub snapshot:Rc<RefCell<LegacyTree>>,
+    pub t:objecttype,
+}
+
+#[repr(C)]
+#[derive(Debug,Serialize,Deserialize)]
+pub struct LegacyTree
+{
+    pub name:String,
+    pub trees:BTreeMap<String,Rc<RefCell<LegacyTree>>>,
+    pub blobs:BTreeMap<String,Rc<RefCell<blob>>>,
+    pub t:objecttype,
+}
+
+impl Default for LegacyCommit {
+    fn default()->Self
+    {
+        Self::new()
+    }
+}
+
+impl LegacyCommit {
+    pub fn new()->Self
+    {
+        LegacyCommit
+        {
+            parents:Vec::new(),
+            mergeparents:Vec::new(),
+            author:String::new(),
+            message:String::new(),
+            snapshot:Rc::new(RefCell::new(LegacyTree::new(String::from(".")))),
+            t:objecttype::commit,
+        }
+    }
+}
+
+impl LegacyTree {
+    pub fn new(name:String)->Self
+    {
+        LegacyTree
+        {
+            name,
+            trees:BTreeMap::new(),
+            blobs:BTreeMap::new(),
+            t:objecttype::tree,
+        }
+    }
+}
+
+//把旧的tree以及它下面的tree和blob写入objects，返回它的SHA1
+pub fn storeTree(node:&Rc<RefCell<LegacyTree>>,objects:&mut Objects)->Result<String,GitcError>
+{
+    let old=node.borrow();
+    let mut res=tree::new(old.name.clone());
+    for (path,file) in old.blobs.iter()
+    {
+        res.blobs.insert(path.clone(),objects.insertBlob(file)?);
+    }
+    for (path,child) in old.trees.iter()
+    {
+        res.trees.insert(path.clone(),storeTree(child,objects)?);
+    }
+    objects.insertTree(&Rc::new(RefCell::new(res)))
+}
+
+//把旧的commit以及它的祖先写入objects，返回它的SHA1，占位commit返回None。
+//版本6之前parents和mergeparents中记录整条祖先链，最后一个才是直接的父commit，所以只沿着最后一个向上找。
+//stored按对象地址记录已经写入的commit，同一个commit被多处引用时只写一次
+pub fn storeCommit(commit:&Rc<RefCell<LegacyCommit>>,objects:&mut Objects,stored:&mut BTreeMap<usize,String>)->Result<Option<String>,GitcError>
+{
+    let key=Rc::as_ptr(commit) as usize;
+    if let Some(id)=stored.get(&key)
+    {
+        return Ok(Some(id.clone()));
+    }
+    let old=commit.borrow();
+    if old.message.is_empty()
+    {
+        return Ok(None);
+    }
+    let mut res=Commit::new();
+    //旧的commit只记录作者的名字，写成没有邮箱、时间为0的签名，和原来的SHA1保持一致
+    res.author=Signature::new(old.author.clone(),String::new(),0,0);
+    res.committer=res.author.clone();
+    res.message=old.message.clone();
+    res.snapshot=storeTree(&old.snapshot,objects)?;
+    if let Some(Some(parent))=old.parents.last()
+    {
+        res.parents.extend(storeCommit(parent,objects,stored)?);
+    }
+    if let Some(Some(parent))=old.mergeparents.last()
+    {
+        res.mergeparents.extend(storeCommit(parent,objects,stored)?);
+    }
+    let id=objects.insertCommit(&Rc::new(RefCell::new(res)))?;
+    stored.insert(key,id.clone());
+    Ok(Some(id))
+}
+
+impl LegacyHEAD {
+    //换成按SHA1引用commit的head，分支指向的commit都写入objects
+    pub fn upgrade(&self,objects:&mut Objects)->Result<HEAD,GitcError>
+    {
+        let mut stored=BTreeMap::new();
+        let mut head=HEAD::new();
+        head.currentBranchName=self.currentBranchName.clone();
+        head.mergeHead=self.mergeHead.clone();
+        for (name,old) in self.branch.iter()
+        {
+            let old=old.borrow();
+            let mut branch=Branch::new(old.name.clone());
+            branch.references=old.references.clone();
+            if let Some(commit)=old.commitpointer.as_ref()
+            {
+                branch.commitpointer=storeCommit(commit,objects,&mut stored)?;
+            }
+            head.branch.insert(name.clone(),Rc::new(RefCell::new(branch)));
+        }
+        Ok(head)
+    }
+}
+
+impl LegacyObjects {
+    //写入所有对象，返回旧的SHA1到新SHA1的映射
+    pub fn store(&self,objects:&mut Objects)->Result<BTreeMap<String,String>,GitcError>
+    {
+        let mut renamed=BTreeMap::new();
+        for (old,file) in self.blobmap.iter()
+        {
+            renamed.insert(old.clone(),objects.insertBlob(file)?);
+        }
+        for (old,node) in self.treemap.iter()
+        {
+            renamed.insert(old.clone(),storeTree(node,objects)?);
+        }
+        let mut stored=BTreeMap::new();
+        for (old,commit) in self.commitmap.iter()
+        {
+            if let Some(id)=storeCommit(commit,objects,&mut stored)?
+            {
+                renamed.insert(old.clone(),id);
+            }
+        }
+        Ok(renamed)
+    }
+}
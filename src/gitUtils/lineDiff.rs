@@ -0,0 +1,493 @@
+//行级diff(Myers、patience、histogram)，结果用DiffOp描述如何从旧序列得到新序列
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum DiffOp
+{
+    //old[old..old+len]与new[new..new+len]相同
+    Equal{old:usize,new:usize,len:usize},
+    //删除old[old..old+len]
+    Delete{old:usize,len:usize},
+    //插入new[new..new+len]
+    Insert{new:usize,len:usize},
+}
+
+//按'\n'切分内容，每一行保留自己的换行符，最后一行可能没有换行符
+pub fn splitLines(contents:&[u8])->Vec<&[u8]>
+{
+    let mut lines=vec![];
+    let mut start=0;
+    for (i,byte) in contents.iter().enumerate()
+    {
+        if *byte==b'\n'
+        {
+            lines.push(&contents[start..i+1]);
+            start=i+1;
+        }
+    }
+    if start<contents.len()
+    {
+        lines.push(&contents[start..]);
+    }
+    lines
+}
+
+//追加一个操作，和上一个同类的操作相连时合并成一个
+fn pushOp(ops:&mut Vec<DiffOp>,op:DiffOp)
+{
+    match (ops.last_mut(),op)
+    {
+        (_,DiffOp::Equal{len:0,..} | DiffOp::Delete{len:0,..} | DiffOp::Insert{len:0,..})=>{}
+        (Some(DiffOp::Equal{len,..}),DiffOp::Equal{len:more,..})
+        | (Some(DiffOp::Delete{len,..}),DiffOp::Delete{len:more,..})
+        | (Some(DiffOp::Insert{len,..}),DiffOp::Insert{len:more,..})=>*len+=more,
+        _=>ops.push(op),
+    }
+}
+
+//最短编辑路径中间的一段snake(x,y)->(u,v)：从两端同时搜索，前后两条路径相遇的地方。
+//a和b都不为空，且开头和结尾不相同
+fn middleSnake<T:PartialEq>(a:&[T],b:&[T])->(usize,usize,usize,usize)
+{
+    let n=a.len() as isize;
+    let m=b.len() as isize;
+    let delta=n-m;
+    let odd=delta.rem_euclid(2)==1;
+    let max=(n+m+1)/2;
+    let offset=max+1;
+    //forward[k]为向前搜索时对角线k上走到的最远的x，backward[k]为从终点倒着搜索时走过的元素个数
+    let mut forward=vec![0isize;(2*max+3) as usize];
+    let mut backward=vec![0isize;(2*max+3) as usize];
+    for d in 0..=max
+    {
+        let mut k=-d;
+        while k<=d
+        {
+            let index=(k+offset) as usize;
+            let startX=if k==-d || (k!=d && forward[index-1]<forward[index+1]) {forward[index+1]} else {forward[index-1]+1};
+            let startY=startX-k;
+            let (mut x,mut y)=(startX,startY);
+            while x<n && y<m && a[x as usize]==b[y as usize]
+            {
+                x+=1;
+                y+=1;
+            }
+            forward[index]=x;
+            //倒着搜索的对角线delta-k上已经走了d-1步
+            let other=delta-k;
+            if odd && other.abs()<d && x+backward[(other+offset) as usize]>=n
+            {
+                return (startX as usize,startY as usize,x as usize,y as usize);
+            }
+            k+=2;
+        }
+        let mut k=-d;
+        while k<=d
+        {
+            let index=(k+offset) as usize;
+            let startX=if k==-d || (k!=d && backward[index-1]<backward[index+1]) {backward[index+1]} else {backward[index-1]+1};
+            let startY=startX-k;
+            let (mut x,mut y)=(startX,startY);
+            while x<n && y<m && a[(n-1-x) as usize]==b[(m-1-y) as usize]
+            {
+                x+=1;
+                y+=1;
+            }
+            backward[index]=x;
+            let other=delta-k;
+            if !odd && other.abs()<=d && x+forward[(other+offset) as usize]>=n
+            {
+                return ((n-x) as usize,(m-y) as usize,(n-startX) as usize,(m-startY) as usize);
+            }
+            k+=2;
+        }
+    }
+    unreachable!("the forward and backward paths always meet")
+}
+
+//对a和b递归地求diff，oldStart和newStart为它们在整个序列中的起点
+fn myersSplit<T:PartialEq>(a:&[T],b:&[T],oldStart:usize,newStart:usize,ops:&mut Vec<DiffOp>)
+{
+    //相同的开头和结尾直接输出
+    let prefix=a.iter().zip(b).take_while(|(x,y)| x==y).count();
+    let suffix=a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x,y)| x==y).count();
+    let innerA=&a[prefix..a.len()-suffix];
+    let innerB=&b[prefix..b.len()-suffix];
+    let (oldInner,newInner)=(oldStart+prefix,newStart+prefix);
+    pushOp(ops,DiffOp::Equal{old:oldStart,new:newStart,len:prefix});
+    if innerA.is_empty() || innerB.is_empty()
+    {
+        pushOp(ops,DiffOp::Delete{old:oldInner,len:innerA.len()});
+        pushOp(ops,DiffOp::Insert{new:newInner,len:innerB.len()});
+    }
+    else
+    {
+        let (x,y,u,v)=middleSnake(innerA,innerB);
+        myersSplit(&innerA[..x],&innerB[..y],oldInner,newInner,ops);
+        pushOp(ops,DiffOp::Equal{old:oldInner+x,new:newInner+y,len:u-x});
+        myersSplit(&innerA[u..],&innerB[v..],oldInner+u,newInner+v,ops);
+    }
+    pushOp(ops,DiffOp::Equal{old:oldStart+a.len()-suffix,new:newStart+b.len()-suffix,len:suffix});
+}
+
+//Myers diff的线性空间版本：先找到最短编辑路径中间的snake，再分别对它前后的两部分递归，
+//内存只和n+m成正比，不需要记录每一步的v用于回溯
+pub fn myers<T:PartialEq>(a:&[T],b:&[T])->Vec<DiffOp>
+{
+    let mut ops=vec![];
+    myersSplit(a,b,0,0,&mut ops);
+    ops
+}
+
+//把diff结果合并成修改区间(旧起点,旧终点,新起点,新终点)，相邻的删除和插入属于同一个区间
+pub fn changedRegions(ops:&[DiffOp])->Vec<(usize,usize,usize,usize)>
+{
+    let mut regions:Vec<(usize,usize,usize,usize)>=vec![];
+    let mut oldPos=0;
+    let mut newPos=0;
+    let mut open:Option<(usize,usize)>=None;
+    for op in ops
+    {
+        match *op
+        {
+            DiffOp::Equal{old,new,len}=>
+            {
+                if let Some((oldStart,newStart))=open.take()
+                {
+                    regions.push((oldStart,old,newStart,new));
+                }
+                oldPos=old+len;
+                newPos=new+len;
+            }
+            DiffOp::Delete{len,..}=>
+            {
+                open.get_or_insert((oldPos,newPos));
+                oldPos+=len;
+            }
+            DiffOp::Insert{len,..}=>
+            {
+                open.get_or_insert((oldPos,newPos));
+                newPos+=len;
+            }
+        }
+    }
+    if let Some((oldStart,newStart))=open
+    {
+        regions.push((oldStart,oldPos,newStart,newPos));
+    }
+    regions
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum DiffAlgorithm
+{
+    Myers,
+    Patience,
+    Histogram,
+}
+
+impl DiffAlgorithm {
+    pub fn fromName(name:&str)->Option<DiffAlgorithm>
+    {
+        match name
+        {
+            "myers" | "default"=>Some(DiffAlgorithm::Myers),
+            "patience"=>Some(DiffAlgorithm::Patience),
+            "histogram"=>Some(DiffAlgorithm::Histogram),
+            _=>None,
+        }
+    }
+}
+
+//用选定的算法比较两个序列
+pub fn diff<T:Eq+Hash>(algorithm:DiffAlgorithm,a:&[T],b:&[T])->Vec<DiffOp>
+{
+    let mut matches=vec![];
+    match algorithm
+    {
+        DiffAlgorithm::Myers=>return myers(a,b),
+        DiffAlgorithm::Patience=>patience(a,b,(0,a.len()),(0,b.len()),&mut matches),
+        DiffAlgorithm::Histogram=>histogram(a,b,(0,a.len()),(0,b.len()),&mut matches),
+    }
+    fromMatches(a.len(),b.len(),&matches)
+}
+
+//把按顺序排列的相同行(旧下标,新下标)转换成DiffOp，同一段修改中先删除后插入
+fn fromMatches(n:usize,m:usize,matches:&[(usize,usize)])->Vec<DiffOp>
+{
+    let mut ops:Vec<DiffOp>=vec![];
+    let mut oldPos=0;
+    let mut newPos=0;
+    for &(x,y) in matches.iter().chain(std::iter::once(&(n,m)))
+    {
+        if x>oldPos
+        {
+            ops.push(DiffOp::Delete{old:oldPos,len:x-oldPos});
+        }
+        if y>newPos
+        {
+            ops.push(DiffOp::Insert{new:newPos,len:y-newPos});
+        }
+        if x==n && y==m
+        {
+            break;
+        }
+        match ops.last_mut()
+        {
+            Some(DiffOp::Equal{old,len,..}) if *old+*len==x && x==oldPos && y==newPos=>*len+=1,
+            _=>ops.push(DiffOp::Equal{old:x,new:y,len:1}),
+        }
+        oldPos=x+1;
+        newPos=y+1;
+    }
+    ops
+}
+
+//去掉区间首尾相同的行，返回剩下的区间和末尾相同的行
+fn trimCommon<T:PartialEq>(a:&[T],b:&[T],old:&mut (usize,usize),new:&mut (usize,usize),matches:&mut Vec<(usize,usize)>)->Vec<(usize,usize)>
+{
+    while old.0<old.1 && new.0<new.1 && a[old.0]==b[new.0]
+    {
+        matches.push((old.0,new.0));
+        old.0+=1;
+        new.0+=1;
+    }
+    let mut suffix=vec![];
+    while old.0<old.1 && new.0<new.1 && a[old.1-1]==b[new.1-1]
+    {
+        old.1-=1;
+        new.1-=1;
+        suffix.push((old.1,new.1));
+    }
+    suffix.reverse();
+    suffix
+}
+
+//找不到锚点时退回到Myers算法
+fn myersRange<T:PartialEq>(a:&[T],b:&[T],old:(usize,usize),new:(usize,usize),matches:&mut Vec<(usize,usize)>)
+{
+    for op in myers(&a[old.0..old.1],&b[new.0..new.1])
+    {
+        if let DiffOp::Equal{old:x,new:y,len}=op
+        {
+            matches.extend((0..len).map(|i| (old.0+x+i,new.0+y+i)));
+        }
+    }
+}
+
+//patience diff：以两边都只出现一次的行中最长的递增序列为锚点，递归比较锚点之间的部分
+fn patience<T:Eq+Hash>(a:&[T],b:&[T],mut old:(usize,usize),mut new:(usize,usize),matches:&mut Vec<(usize,usize)>)
+{
+    let suffix=trimCommon(a,b,&mut old,&mut new,matches);
+    if old.0<old.1 && new.0<new.1
+    {
+        //每一行在两边出现的次数和位置
+        let mut counts:HashMap<&T,(usize,usize,usize)>=HashMap::new();
+        for (i,line) in a.iter().enumerate().take(old.1).skip(old.0)
+        {
+            let entry=counts.entry(line).or_insert((0,0,0));
+            entry.0+=1;
+            entry.2=i;
+        }
+        let mut unique:Vec<(usize,usize)>=vec![];
+        let mut seen:HashMap<&T,usize>=HashMap::new();
+        for line in b.iter().take(new.1).skip(new.0)
+        {
+            *seen.entry(line).or_insert(0)+=1;
+        }
+        for (j,line) in b.iter().enumerate().take(new.1).skip(new.0)
+        {
+            if let Some(&(1,_,i))=counts.get(line)
+            {
+                if seen[line]==1
+                {
+                    unique.push((i,j));
+                }
+            }
+        }
+        //unique按新下标排列，取旧下标的最长递增子序列
+        let mut tails:Vec<usize>=vec![];
+        let mut prev:Vec<Option<usize>>=vec![None;unique.len()];
+        for (index,&(i,_)) in unique.iter().enumerate()
+        {
+            let pos=tails.partition_point(|&t| unique[t].0<i);
+            if pos>0
+            {
+                prev[index]=Some(tails[pos-1]);
+            }
+            if pos==tails.len()
+            {
+                tails.push(index);
+            }
+            else
+            {
+                tails[pos]=index;
+            }
+        }
+        let mut anchors=vec![];
+        let mut current=tails.last().copied();
+        while let Some(index)=current
+        {
+            anchors.push(unique[index]);
+            current=prev[index];
+        }
+        anchors.reverse();
+
+        if anchors.is_empty()
+        {
+            myersRange(a,b,old,new,matches);
+        }
+        else
+        {
+            let mut start=(old.0,new.0);
+            for &(i,j) in anchors.iter()
+            {
+                patience(a,b,(start.0,i),(start.1,j),matches);
+                matches.push((i,j));
+                start=(i+1,j+1);
+            }
+            patience(a,b,(start.0,old.1),(start.1,new.1),matches);
+        }
+    }
+    matches.extend(suffix);
+}
+
+//histogram diff：以旧序列中出现次数最少的共同行所在的最长相同区间为锚点，递归比较两侧
+fn histogram<T:Eq+Hash>(a:&[T],b:&[T],mut old:(usize,usize),mut new:(usize,usize),matches:&mut Vec<(usize,usize)>)
+{
+    let suffix=trimCommon(a,b,&mut old,&mut new,matches);
+    if old.0<old.1 && new.0<new.1
+    {
+        let mut positions:HashMap<&T,Vec<usize>>=HashMap::new();
+        for (i,line) in a.iter().enumerate().take(old.1).skip(old.0)
+        {
+            positions.entry(line).or_default().push(i);
+        }
+        //(出现次数,区间长度,旧起点,新起点)
+        let mut best:Option<(usize,usize,usize,usize)>=None;
+        for (j,line) in b.iter().enumerate().take(new.1).skip(new.0)
+        {
+            let Some(found)=positions.get(line) else {continue};
+            if best.is_some_and(|(count,..)| found.len()>count)
+            {
+                continue;
+            }
+            for &i in found
+            {
+                let (mut x,mut y)=(i,j);
+                while x>old.0 && y>new.0 && a[x-1]==b[y-1]
+                {
+                    x-=1;
+                    y-=1;
+                }
+                let mut len=0;
+                while x+len<old.1 && y+len<new.1 && a[x+len]==b[y+len]
+                {
+                    len+=1;
+                }
+                let better=match best
+                {
+                    None=>true,
+                    Some((count,bestLen,..))=>found.len()<count || len>bestLen,
+                };
+                if better
+                {
+                    best=Some((found.len(),len,x,y));
+                }
+            }
+        }
+        match best
+        {
+            None=>myersRange(a,b,old,new,matches),
+            Some((_,len,x,y))=>
+            {
+                histogram(a,b,(old.0,x),(new.0,y),matches);
+                matches.extend((0..len).map(|i| (x+i,y+i)));
+                histogram(a,b,(x+len,old.1),(y+len,new.1),matches);
+            }
+        }
+    }
+    matches.extend(suffix);
+}
+
+//内容的前8000个字节中出现'\0'就当作二进制文件
+pub fn isBinary(contents:&[u8])->bool
+{
+    contents.iter().take(8000).any(|byte| *byte==0)
+}
+
+//unified diff中"@@ -start,count +start,count @@"的一半，count为1时省略
+fn hunkRange(start:usize,count:usize)->String
+{
+    match count
+    {
+        0=>format!("{},0",start),
+        1=>format!("{}",start+1),
+        _=>format!("{},{}",start+1,count),
+    }
+}
+
+fn pushLine(out:&mut String,prefix:char,line:&[u8])
+{
+    out.push(prefix);
+    out.push_str(&String::from_utf8_lossy(line));
+    if !line.ends_with(b"\n")
+    {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+//生成两个内容之间的unified diff的所有hunk，每个修改前后保留context行上下文，内容相同时返回空字符串
+pub fn unifiedHunks(old:&[u8],new:&[u8],context:usize,algorithm:DiffAlgorithm)->String
+{
+    let a=splitLines(old);
+    let b=splitLines(new);
+    let regions=changedRegions(&diff(algorithm,&a,&b));
+
+    //上下文会重叠的修改放进同一个hunk
+    let mut groups:Vec<Vec<(usize,usize,usize,usize)>>=vec![];
+    for region in regions
+    {
+        match groups.last_mut()
+        {
+            Some(group) if region.0-group[group.len()-1].1<=2*context=>group.push(region),
+            _=>groups.push(vec![region]),
+        }
+    }
+
+    let mut out=String::new();
+    for group in groups
+    {
+        let first=group[0];
+        let last=group[group.len()-1];
+        let oldStart=first.0.saturating_sub(context);
+        let newStart=first.2-(first.0-oldStart);
+        let oldEnd=(last.1+context).min(a.len());
+        let newEnd=last.3+(oldEnd-last.1);
+        out.push_str(&format!("@@ -{} +{} @@\n",hunkRange(oldStart,oldEnd-oldStart),hunkRange(newStart,newEnd-newStart)));
+        let mut pos=oldStart;
+        for (oldFrom,oldTo,newFrom,newTo) in group
+        {
+            for line in &a[pos..oldFrom]
+            {
+                pushLine(&mut out,' ',line);
+            }
+            for line in &a[oldFrom..oldTo]
+            {
+                pushLine(&mut out,'-',line);
+            }
+            for line in &b[newFrom..newTo]
+            {
+                pushLine(&mut out,'+',line);
+            }
+            pos=oldTo;
+        }
+        for line in &a[pos..oldEnd]
+        {
+            pushLine(&mut out,' ',line);
+        }
+    }
+    out
+}
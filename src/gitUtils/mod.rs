@@ -1 +1,13 @@
-pub mod gitUtils;
\ No newline at end of file
+pub mod gitUtils;
+pub mod gitError;
+pub mod lineDiff;
+pub mod threeWay;
+pub mod objectStore;
+pub mod pack;
+pub mod wildmatch;
+pub mod ignore;
+pub mod index;
+pub mod legacy;
+pub mod signature;
+pub mod config;
+pub mod repository;
@@ -0,0 +1,478 @@
+//./.gitc/objects下按SHA1存放的对象，格式和git的loose object相同：zlib压缩的"<类型> <长度>\0<内容>"，
+//repack之后对象放在./.gitc/objects/pack中。Objects中的三个map只是读写这些文件时的缓存。
+//这里的路径都相对工作区的根目录root，和当前目录无关
+use crate::gitUtils::gitUtils::{Objects,Commit,tree,blob,objecttype};
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::signature::Signature;
+use crate::gitUtils::index::MODE_FILE;
+use crate::gitUtils::pack::{findPacked,readPacked,packedKind,loadPacks,PackIndex};
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::fs;
+use std::io::{Read,Write};
+use std::path::{Path,PathBuf};
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use walkdir::WalkDir;
+use sha1::{Sha1, Digest};
+
+pub const OBJECTS_DIR:&str="./.gitc/objects";
+const HEX:[char;16]=['0','1','2','3','4','5','6','7','8','9','a','b','c','d','e','f'];
+
+pub fn objectPath(root:&Path,id:&str)->PathBuf
+{
+    root.join(OBJECTS_DIR).join(&id[0..2]).join(&id[2..])
+}
+
+pub fn getSHA1(data:&[u8])->String
+{
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    let mut res=String::new();
+    let result = hasher.finalize();
+    for i in result.iter()
+    {
+        let a1:u8=15;
+        let a2:u8=240;
+        let r1=i&a1;
+        let r2=(i&a2)/16;
+
+        res.push(HEX[r2 as usize]);
+        res.push(HEX[r1 as usize]);
+    }
+    res
+}
+
+//和git相同的对象SHA1："<类型> <长度>\0"加上对象内容
+pub fn hashObject(kind:&str,body:&[u8])->String
+{
+    let mut data=format!("{} {}\0",kind,body.len()).into_bytes();
+    data.extend_from_slice(body);
+    getSHA1(&data)
+}
+
+pub fn hashBlob(contents:&[u8])->String
+{
+    hashObject("blob",contents)
+}
+
+//没有打包的对象
+pub fn isLoose(root:&Path,id:&str)->bool
+{
+    id.len()==40 && objectPath(root,id).is_file()
+}
+
+//下面三个函数每次调用都会重新读pack的index，Repository中应当使用objects上的同名方法
+pub fn hasObject(root:&Path,id:&str)->bool
+{
+    Objects::at(root).hasObject(id)
+}
+
+pub fn writeObject(root:&Path,kind:&str,body:&[u8])->Result<String,GitcError>
+{
+    Objects::at(root).writeObject(kind,body)
+}
+
+pub fn readObject(root:&Path,id:&str)->Result<(String,Vec<u8>),GitcError>
+{
+    Objects::at(root).readObject(id)
+}
+
+//读出一个loose object的类型和内容
+fn readLoose(root:&Path,id:&str)->Result<(String,Vec<u8>),GitcError>
+{
+    let corrupt=|| GitcError::CorruptStore(format!("bad object {}",id));
+    let data=inflate(&fs::read(objectPath(root,id))?).map_err(|_| corrupt())?;
+    let nul=data.iter().position(|byte| *byte==0).ok_or_else(corrupt)?;
+    let header=String::from_utf8_lossy(&data[..nul]).to_string();
+    let (kind,len)=header.split_once(' ').ok_or_else(corrupt)?;
+    let body=data[nul+1..].to_vec();
+    if len.parse::<usize>().ok()!=Some(body.len())
+    {
+        return Err(corrupt());
+    }
+    Ok((kind.to_string(),body))
+}
+
+//解压对象文件，没有压缩的旧对象原样返回
+fn inflate(data:&[u8])->std::io::Result<Vec<u8>>
+{
+    if [&b"blob "[..],b"tree ",b"commit "].iter().any(|kind| data.starts_with(kind))
+    {
+        return Ok(data.to_vec());
+    }
+    let mut res=vec![];
+    ZlibDecoder::new(data).read_to_end(&mut res)?;
+    Ok(res)
+}
+
+//./.gitc/objects中对象(包括pack中的对象)的个数，以及压缩前后的总大小
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct StoreStats
+{
+    pub count:usize,
+    pub rawSize:u64,
+    pub compressedSize:u64,
+}
+
+pub fn storeStats(root:&Path)->Result<StoreStats,GitcError>
+{
+    let mut stats=StoreStats::default();
+    for entry in WalkDir::new(root.join(OBJECTS_DIR)).min_depth(2).max_depth(2)
+    {
+        let entry=entry.map_err(|err| GitcError::Io(err.into()))?;
+        if !entry.file_type().is_file() || entry.path().extension().is_some()
+        {
+            continue;
+        }
+        let data=fs::read(entry.path())?;
+        stats.count+=1;
+        stats.compressedSize+=data.len() as u64;
+        stats.rawSize+=inflate(&data)?.len() as u64;
+    }
+    //pack中的对象按loose object的格式计算压缩前的大小，header中的类型是它在pack中的实际类型
+    let packs=loadPacks(root)?;
+    for pack in packs.iter()
+    {
+        for entry in pack.entries.iter()
+        {
+            let kind=packedKind(&packs,&entry.id)?.ok_or_else(|| GitcError::CorruptStore(format!("missing delta base of {}",entry.id)))?;
+            stats.count+=1;
+            stats.rawSize+=entry.size+format!("{} {}\0",kind,entry.size).len() as u64;
+        }
+        stats.compressedSize+=fs::metadata(&pack.path)?.len()+fs::metadata(pack.path.with_extension("idx"))?.len();
+    }
+    Ok(stats)
+}
+
+//路径的最后一段，tree对象中只记录这一段
+fn baseName(path:&str)->&str
+{
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn childPath(dir:&str,name:&str)->String
+{
+    format!("{}/{}",dir,name)
+}
+
+//40位十六进制的SHA1转换成tree对象中的20字节
+pub fn idBytes(id:&str)->Result<Vec<u8>,GitcError>
+{
+    let corrupt=|| GitcError::CorruptStore(format!("bad object id '{}'",id));
+    if id.len()!=40 || !id.bytes().all(|byte| byte.is_ascii_hexdigit())
+    {
+        return Err(corrupt());
+    }
+    (0..20).map(|i| u8::from_str_radix(&id[2*i..2*i+2],16).map_err(|_| corrupt())).collect()
+}
+
+//git的tree对象格式：每一项为"<mode> <name>\0<20字节SHA1>"，按名字排序，文件夹按"名字/"参与排序。
+//记录的SHA1不是40位十六进制时返回CorruptStore
+pub fn treeObject(node:&Rc<RefCell<tree>>)->Result<Vec<u8>,GitcError>
+{
+    let node=node.borrow();
+    let mut entries:Vec<(String,String,String)>=vec![];
+    for (key,value) in node.blobs.iter()
+    {
+        let mode=node.modes.get(key).copied().unwrap_or(MODE_FILE);
+        entries.push((baseName(key).to_string(),format!("{:o}",mode),value.clone()));
+    }
+    for (key,value) in node.trees.iter()
+    {
+        entries.push((format!("{}/",baseName(key)),String::from("40000"),value.clone()));
+    }
+    entries.sort();
+    let mut res=vec![];
+    for (name,mode,id) in entries
+    {
+        res.extend_from_slice(format!("{} {}\0",mode,name.trim_end_matches('/')).as_bytes());
+        res.extend(idBytes(&id)?);
+    }
+    Ok(res)
+}
+
+pub fn treeId(node:&Rc<RefCell<tree>>)->Result<String,GitcError>
+{
+    Ok(hashObject("tree",&treeObject(node)?))
+}
+
+//git的commit对象格式：tree、直接的父commit、作者、提交者和提交信息
+pub fn commitObject(commit:&Rc<RefCell<Commit>>)->Result<Vec<u8>,GitcError>
+{
+    let commit=commit.borrow();
+    let mut res=format!("tree {}\n",commit.snapshot);
+    for parent in commit.directParents()
+    {
+        res.push_str(&format!("parent {}\n",parent));
+    }
+    res.push_str(&format!("author {}\ncommitter {}\n\n{}\n",commit.author.encode(),commit.committer.encode(),commit.message));
+    Ok(res.into_bytes())
+}
+
+pub fn commitId(commit:&Rc<RefCell<Commit>>)->Result<String,GitcError>
+{
+    Ok(hashObject("commit",&commitObject(commit)?))
+}
+
+//复制一个tree，并把它记录的子tree和blob的路径从原来的位置换到name下
+pub fn relocateTree(node:&Rc<RefCell<tree>>,name:&str)->Rc<RefCell<tree>>
+{
+    let old=node.borrow().name.clone();
+    let moved=|path:&str| format!("{}{}",name,&path[old.len()..]);
+    let mut res=tree::new(name.to_string());
+    for (key,value) in node.borrow().blobs.iter()
+    {
+        res.blobs.insert(moved(key),value.clone());
+    }
+    for (key,value) in node.borrow().modes.iter()
+    {
+        res.modes.insert(moved(key),*value);
+    }
+    for (key,value) in node.borrow().trees.iter()
+    {
+        res.trees.insert(moved(key),value.clone());
+    }
+    Rc::new(RefCell::new(res))
+}
+
+impl Objects {
+    //写入对象时使用的压缩级别
+    pub fn level(&self)->Compression
+    {
+        self.compression.map_or(Compression::default(),Compression::new)
+    }
+
+    //root下所有pack的index，第一次用到时读入并缓存
+    pub fn packs(&self)->Result<Rc<Vec<PackIndex>>,GitcError>
+    {
+        if let Some(packs)=self.packCache.borrow().as_ref()
+        {
+            return Ok(packs.clone());
+        }
+        let packs=Rc::new(loadPacks(&self.root)?);
+        *self.packCache.borrow_mut()=Some(packs.clone());
+        Ok(packs)
+    }
+
+    //写入或删除pack之后丢掉缓存的index
+    pub fn forgetPacks(&self)
+    {
+        *self.packCache.borrow_mut()=None;
+    }
+
+    //./.gitc/objects中是否有这个对象，不看缓存的map
+    pub fn hasObject(&self,id:&str)->bool
+    {
+        isLoose(&self.root,id) || (id.len()==40 && self.packs().is_ok_and(|packs| findPacked(&packs,id).is_some()))
+    }
+
+    //按core.compression写入一个对象并返回它的SHA1，已经存在的对象不会重复写入。
+    //先写临时文件再改名，写到一半中断也不会留下损坏的对象
+    pub fn writeObject(&self,kind:&str,body:&[u8])->Result<String,GitcError>
+    {
+        let id=hashObject(kind,body);
+        if self.hasObject(&id)
+        {
+            return Ok(id);
+        }
+        let path=objectPath(&self.root,&id);
+        if let Some(dir)=path.parent()
+        {
+            fs::create_dir_all(dir)?;
+        }
+        let mut encoder=ZlibEncoder::new(Vec::new(),self.level());
+        encoder.write_all(format!("{} {}\0",kind,body.len()).as_bytes())?;
+        encoder.write_all(body)?;
+        let temp=path.with_extension("tmp");
+        fs::write(&temp,encoder.finish()?)?;
+        fs::rename(&temp,&path)?;
+        Ok(id)
+    }
+
+    //读出一个对象的类型和内容，先找loose object，再找pack
+    pub fn readObject(&self,id:&str)->Result<(String,Vec<u8>),GitcError>
+    {
+        if isLoose(&self.root,id)
+        {
+            return readLoose(&self.root,id);
+        }
+        if id.len()==40
+        {
+            if let Some(found)=readPacked(&self.packs()?,id)?
+            {
+                return Ok(found);
+            }
+        }
+        Err(GitcError::ObjectNotFound(id.to_string()))
+    }
+
+    //对象的类型，缓存和./.gitc/objects中都没有时返回None
+    pub fn kindOf(&self,id:&str)->Option<objecttype>
+    {
+        if self.blobmap.contains_key(id)
+        {
+            return Some(objecttype::blob);
+        }
+        if self.treemap.contains_key(id)
+        {
+            return Some(objecttype::tree);
+        }
+        if self.commitmap.contains_key(id)
+        {
+            return Some(objecttype::commit);
+        }
+        match self.readObject(id).ok()?.0.as_str()
+        {
+            "blob"=>Some(objecttype::blob),
+            "tree"=>Some(objecttype::tree),
+            "commit"=>Some(objecttype::commit),
+            _=>None,
+        }
+    }
+
+    pub fn contains(&self,id:&str)->bool
+    {
+        self.blobmap.contains_key(id) || self.treemap.contains_key(id) || self.commitmap.contains_key(id) || self.hasObject(id)
+    }
+
+    fn readKind(&self,id:&str,kind:&str)->Result<Vec<u8>,GitcError>
+    {
+        let (found,body)=self.readObject(id)?;
+        if found!=kind
+        {
+            return Err(GitcError::CorruptStore(format!("{} is a {}, not a {}",id,found,kind)));
+        }
+        Ok(body)
+    }
+
+    //按SHA1读出blob，name是它在工作区中的路径
+    pub fn loadBlob(&self,id:&str,name:&str)->Result<Rc<RefCell<blob>>,GitcError>
+    {
+        let mut file=blob::new(name.to_string());
+        match self.blobmap.get(id)
+        {
+            Some(cached) if cached.borrow().name==name=>return Ok(cached.clone()),
+            Some(cached)=>file.contents=cached.borrow().contents.clone(),
+            None=>file.contents=self.readKind(id,"blob")?,
+        }
+        Ok(Rc::new(RefCell::new(file)))
+    }
+
+    //按SHA1读出blob的内容
+    pub fn blobContents(&self,id:&str)->Result<Vec<u8>,GitcError>
+    {
+        match self.blobmap.get(id)
+        {
+            Some(cached)=>Ok(cached.borrow().contents.clone()),
+            None=>self.readKind(id,"blob"),
+        }
+    }
+
+    //按SHA1读出tree，name是它在工作区中的路径，子tree和blob只记录SHA1，用到时再读出
+    pub fn loadTree(&self,id:&str,name:&str)->Result<Rc<RefCell<tree>>,GitcError>
+    {
+        //内容相同的文件夹SHA1相同，缓存中的可能是另一个路径下的tree
+        if let Some(cached)=self.treemap.get(id)
+        {
+            if cached.borrow().name==name
+            {
+                return Ok(cached.clone());
+            }
+            return Ok(relocateTree(cached,name));
+        }
+        let body=self.readKind(id,"tree")?;
+        let corrupt=|| GitcError::CorruptStore(format!("bad tree {}",id));
+        let mut node=tree::new(name.to_string());
+        let mut rest=&body[..];
+        while !rest.is_empty()
+        {
+            let nul=rest.iter().position(|byte| *byte==0).ok_or_else(corrupt)?;
+            let entry=String::from_utf8_lossy(&rest[..nul]).to_string();
+            let (mode,entryName)=entry.split_once(' ').ok_or_else(corrupt)?;
+            let childId:String=rest.get(nul+1..nul+21).ok_or_else(corrupt)?.iter().map(|byte| format!("{:02x}",byte)).collect();
+            rest=&rest[nul+21..];
+            let path=childPath(name,entryName);
+            if mode=="40000"
+            {
+                node.trees.insert(path,childId);
+            }
+            else
+            {
+                let mode=u32::from_str_radix(mode,8).map_err(|_| corrupt())?;
+                if mode!=MODE_FILE
+                {
+                    node.modes.insert(path.clone(),mode);
+                }
+                node.blobs.insert(path,childId);
+            }
+        }
+        Ok(Rc::new(RefCell::new(node)))
+    }
+
+    //按SHA1读出commit，父commit和快照只记录SHA1
+    pub fn loadCommit(&self,id:&str)->Result<Rc<RefCell<Commit>>,GitcError>
+    {
+        if let Some(cached)=self.commitmap.get(id)
+        {
+            return Ok(cached.clone());
+        }
+        let body=String::from_utf8_lossy(&self.readKind(id,"commit")?).to_string();
+        let (headers,message)=body.split_once("\n\n").ok_or_else(|| GitcError::CorruptStore(format!("bad commit {}",id)))?;
+        let mut commit=Commit::new();
+        commit.message=message.strip_suffix('\n').unwrap_or(message).to_string();
+        for line in headers.lines()
+        {
+            if let Some(treeid)=line.strip_prefix("tree ")
+            {
+                commit.snapshot=treeid.to_string();
+            }
+            else if let Some(parent)=line.strip_prefix("parent ")
+            {
+                //第一个parent是第一父commit，之后的都是被合并进来的
+                if commit.parents.is_empty()
+                {
+                    commit.parents.push(parent.to_string());
+                }
+                else
+                {
+                    commit.mergeparents.push(parent.to_string());
+                }
+            }
+            else if let Some(author)=line.strip_prefix("author ")
+            {
+                commit.author=Signature::decode(author)?;
+            }
+            else if let Some(committer)=line.strip_prefix("committer ")
+            {
+                commit.committer=Signature::decode(committer)?;
+            }
+        }
+        Ok(Rc::new(RefCell::new(commit)))
+    }
+
+    //写入blob并记录进缓存，返回它的SHA1
+    pub fn insertBlob(&mut self,file:&Rc<RefCell<blob>>)->Result<String,GitcError>
+    {
+        let id=self.writeObject("blob",&file.borrow().contents)?;
+        self.blobmap.insert(id.clone(),file.clone());
+        Ok(id)
+    }
+
+    //写入tree，它下面的tree和blob应当已经写入
+    pub fn insertTree(&mut self,node:&Rc<RefCell<tree>>)->Result<String,GitcError>
+    {
+        let id=self.writeObject("tree",&treeObject(node)?)?;
+        self.treemap.insert(id.clone(),node.clone());
+        Ok(id)
+    }
+
+    pub fn insertCommit(&mut self,commit:&Rc<RefCell<Commit>>)->Result<String,GitcError>
+    {
+        let id=self.writeObject("commit",&commitObject(commit)?)?;
+        self.commitmap.insert(id.clone(),commit.clone());
+        Ok(id)
+    }
+}
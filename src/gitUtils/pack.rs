@@ -0,0 +1,541 @@
+//把多个对象打包进./.gitc/objects/pack中的pack文件，相似的对象只保存相对另一个对象的delta。
+//pack文件："PACK"、版本号、对象个数，之后每个对象为"<类型><长度>[base的SHA1]<zlib压缩的内容>"，最后是前面所有内容的SHA1；
+//index文件："GIDX"、版本号、对象个数，之后按SHA1排序的每个对象为"<20字节SHA1><在pack中的偏移><原始长度>"，最后是pack的SHA1
+use crate::gitUtils::gitError::GitcError;
+use crate::gitUtils::objectStore::{hashObject,getSHA1,idBytes};
+
+use std::collections::{BTreeSet,HashMap};
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader,Read,Seek,SeekFrom,Write};
+use std::path::{Path,PathBuf};
+use flate2::Compression;
+use flate2::bufread::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+pub const PACK_DIR:&str="./.gitc/objects/pack";
+const PACK_VERSION:u32=1;
+//delta只保存相对base的改动，它的长度超过原对象的一半时直接保存原对象
+const DELTA_WINDOW:usize=10;
+const MAX_DELTA_DEPTH:usize=50;
+//找相同内容时使用的块长度，短于它的相同内容直接作为插入的数据
+const BLOCK:usize=16;
+
+const KIND_COMMIT:u8=1;
+const KIND_TREE:u8=2;
+const KIND_BLOB:u8=3;
+const KIND_DELTA:u8=7;
+
+fn kindCode(kind:&str)->Result<u8,GitcError>
+{
+    match kind
+    {
+        "commit"=>Ok(KIND_COMMIT),
+        "tree"=>Ok(KIND_TREE),
+        "blob"=>Ok(KIND_BLOB),
+        _=>Err(GitcError::CorruptStore(format!("unknown object type {}",kind))),
+    }
+}
+
+fn kindName(code:u8)->Option<&'static str>
+{
+    match code
+    {
+        KIND_COMMIT=>Some("commit"),
+        KIND_TREE=>Some("tree"),
+        KIND_BLOB=>Some("blob"),
+        _=>None,
+    }
+}
+
+fn corrupt(what:&str)->GitcError
+{
+    GitcError::CorruptStore(format!("bad pack: {}",what))
+}
+
+//小端的7位一组变长整数，最高位表示后面还有字节
+fn pushVarint(out:&mut Vec<u8>,mut value:usize)
+{
+    loop
+    {
+        let byte=(value&0x7f) as u8;
+        value>>=7;
+        if value==0
+        {
+            out.push(byte);
+            return;
+        }
+        out.push(byte|0x80);
+    }
+}
+
+fn readVarint(data:&[u8],pos:&mut usize)->Result<usize,GitcError>
+{
+    let mut rest=data.get(*pos..).unwrap_or(&[]);
+    let value=readVarintFrom(&mut rest)?;
+    *pos=data.len()-rest.len();
+    Ok(value)
+}
+
+fn readByte(reader:&mut impl Read,what:&str)->Result<u8,GitcError>
+{
+    let mut byte=[0u8];
+    reader.read_exact(&mut byte).map_err(|_| corrupt(what))?;
+    Ok(byte[0])
+}
+
+fn readVarintFrom(reader:&mut impl Read)->Result<usize,GitcError>
+{
+    let mut value=0usize;
+    let mut shift=0;
+    loop
+    {
+        let byte=readByte(reader,"truncated varint")?;
+        value|=((byte&0x7f) as usize)<<shift;
+        if byte&0x80==0
+        {
+            return Ok(value);
+        }
+        shift+=7;
+        if shift>=usize::BITS
+        {
+            return Err(corrupt("varint overflow"));
+        }
+    }
+}
+
+fn hexId(bytes:&[u8])->String
+{
+    bytes.iter().map(|byte| format!("{:02x}",byte)).collect()
+}
+
+//和git相同的delta格式：base长度、目标长度，之后是copy指令(最高位为1，后面跟着偏移和长度中非0的字节)
+//和insert指令(1到127，后面跟着这么多字节的数据)
+fn pushCopy(out:&mut Vec<u8>,mut offset:usize,mut len:usize)
+{
+    while len>0
+    {
+        let size=len.min(0xffff);
+        let mut op=0x80u8;
+        let mut args=vec![];
+        for i in 0..4
+        {
+            let byte=((offset>>(8*i))&0xff) as u8;
+            if byte!=0
+            {
+                op|=1<<i;
+                args.push(byte);
+            }
+        }
+        for i in 0..2
+        {
+            let byte=((size>>(8*i))&0xff) as u8;
+            if byte!=0
+            {
+                op|=1<<(4+i);
+                args.push(byte);
+            }
+        }
+        out.push(op);
+        out.extend(args);
+        offset+=size;
+        len-=size;
+    }
+}
+
+fn pushInsert(out:&mut Vec<u8>,data:&[u8])
+{
+    for chunk in data.chunks(127)
+    {
+        out.push(chunk.len() as u8);
+        out.extend_from_slice(chunk);
+    }
+}
+
+//生成把base变成target的delta
+pub fn createDelta(base:&[u8],target:&[u8])->Vec<u8>
+{
+    let mut out=vec![];
+    pushVarint(&mut out,base.len());
+    pushVarint(&mut out,target.len());
+
+    let mut blocks:HashMap<&[u8],Vec<usize>>=HashMap::new();
+    for start in (0..base.len().saturating_sub(BLOCK-1)).step_by(BLOCK)
+    {
+        blocks.entry(&base[start..start+BLOCK]).or_default().push(start);
+    }
+    let mut pending=vec![];
+    let mut pos=0;
+    while pos<target.len()
+    {
+        let mut best:(usize,usize)=(0,0);
+        if pos+BLOCK<=target.len()
+        {
+            for &start in blocks.get(&target[pos..pos+BLOCK]).into_iter().flatten()
+            {
+                let mut len=BLOCK;
+                while start+len<base.len() && pos+len<target.len() && base[start+len]==target[pos+len]
+                {
+                    len+=1;
+                }
+                if len>best.1
+                {
+                    best=(start,len);
+                }
+            }
+        }
+        if best.1>=BLOCK
+        {
+            //相同的内容向前延伸，收回刚放进插入数据的字节
+            let (mut start,mut len)=best;
+            while start>0 && !pending.is_empty() && base[start-1]==*pending.last().unwrap_or(&0)
+            {
+                pending.pop();
+                start-=1;
+                len+=1;
+            }
+            pushInsert(&mut out,&pending);
+            pending.clear();
+            pushCopy(&mut out,start,len);
+            pos+=best.1;
+        }
+        else
+        {
+            pending.push(target[pos]);
+            pos+=1;
+        }
+    }
+    pushInsert(&mut out,&pending);
+    out
+}
+
+//把delta作用在base上得到目标内容
+pub fn applyDelta(base:&[u8],delta:&[u8])->Result<Vec<u8>,GitcError>
+{
+    let mut pos=0;
+    if readVarint(delta,&mut pos)?!=base.len()
+    {
+        return Err(corrupt("delta base size mismatch"));
+    }
+    let size=readVarint(delta,&mut pos)?;
+    let mut out=Vec::with_capacity(size);
+    while pos<delta.len()
+    {
+        let op=delta[pos];
+        pos+=1;
+        if op&0x80!=0
+        {
+            let mut offset=0usize;
+            let mut len=0usize;
+            for i in 0..4
+            {
+                if op&(1<<i)!=0
+                {
+                    offset|=(*delta.get(pos).ok_or_else(|| corrupt("truncated copy"))? as usize)<<(8*i);
+                    pos+=1;
+                }
+            }
+            for i in 0..3
+            {
+                if op&(1<<(4+i))!=0
+                {
+                    len|=(*delta.get(pos).ok_or_else(|| corrupt("truncated copy"))? as usize)<<(8*i);
+                    pos+=1;
+                }
+            }
+            if len==0
+            {
+                len=0x10000;
+            }
+            out.extend_from_slice(base.get(offset..offset+len).ok_or_else(|| corrupt("copy out of range"))?);
+        }
+        else if op!=0
+        {
+            let len=op as usize;
+            out.extend_from_slice(delta.get(pos..pos+len).ok_or_else(|| corrupt("truncated insert"))?);
+            pos+=len;
+        }
+        else
+        {
+            return Err(corrupt("reserved delta opcode"));
+        }
+    }
+    if out.len()!=size
+    {
+        return Err(corrupt("delta result size mismatch"));
+    }
+    Ok(out)
+}
+
+fn deflate(data:&[u8],level:Compression)->Result<Vec<u8>,GitcError>
+{
+    let mut encoder=ZlibEncoder::new(Vec::new(),level);
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+//一个对象在pack中的位置
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct PackEntry
+{
+    pub id:String,
+    pub offset:u64,
+    pub size:u64,
+}
+
+//repack得到的pack中对象的个数和其中保存为delta的个数
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct PackSummary
+{
+    pub objects:usize,
+    pub deltas:usize,
+}
+
+//把对象(类型,内容)按level压缩写成一个新的pack和index，返回pack文件的路径
+pub fn writePack(root:&Path,objects:&[(String,Vec<u8>)],level:Compression)->Result<(PathBuf,PackSummary),GitcError>
+{
+    //同类型的对象按长度从大到小排列，每个对象在它前面的窗口中找delta最短的base
+    let mut order:Vec<usize>=(0..objects.len()).collect();
+    order.sort_by(|&a,&b| objects[a].0.cmp(&objects[b].0).then(objects[b].1.len().cmp(&objects[a].1.len())));
+
+    let mut data=b"PACK".to_vec();
+    data.extend_from_slice(&PACK_VERSION.to_be_bytes());
+    data.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+    let mut entries:Vec<PackEntry>=vec![];
+    let mut depth:HashMap<usize,usize>=HashMap::new();
+    let mut summary=PackSummary{objects:objects.len(),deltas:0};
+    for (rank,&index) in order.iter().enumerate()
+    {
+        let (kind,body)=&objects[index];
+        let id=hashObject(kind,body);
+        let mut best:Option<(usize,Vec<u8>)>=None;
+        for &candidate in order[rank.saturating_sub(DELTA_WINDOW)..rank].iter().rev()
+        {
+            let (baseKind,base)=&objects[candidate];
+            if baseKind!=kind || depth.get(&candidate).copied().unwrap_or(0)>=MAX_DELTA_DEPTH
+            {
+                continue;
+            }
+            let delta=createDelta(base,body);
+            if delta.len()<body.len()/2 && best.as_ref().is_none_or(|(_,shortest)| delta.len()<shortest.len())
+            {
+                best=Some((candidate,delta));
+            }
+        }
+        entries.push(PackEntry{id,offset:data.len() as u64,size:body.len() as u64});
+        match best
+        {
+            Some((base,delta))=>
+            {
+                data.push(KIND_DELTA);
+                pushVarint(&mut data,delta.len());
+                data.extend(idBytes(&hashObject(&objects[base].0,&objects[base].1))?);
+                data.extend(deflate(&delta,level)?);
+                depth.insert(index,depth.get(&base).copied().unwrap_or(0)+1);
+                summary.deltas+=1;
+            }
+            None=>
+            {
+                data.push(kindCode(kind)?);
+                pushVarint(&mut data,body.len());
+                data.extend(deflate(body,level)?);
+            }
+        }
+    }
+    let checksum=getSHA1(&data);
+    data.extend(idBytes(&checksum)?);
+
+    entries.sort_by(|a,b| a.id.cmp(&b.id));
+    let mut index=b"GIDX".to_vec();
+    index.extend_from_slice(&PACK_VERSION.to_be_bytes());
+    index.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for entry in entries.iter()
+    {
+        index.extend(idBytes(&entry.id)?);
+        index.extend_from_slice(&entry.offset.to_be_bytes());
+        index.extend_from_slice(&entry.size.to_be_bytes());
+    }
+    index.extend(idBytes(&checksum)?);
+
+    let packDir=root.join(PACK_DIR);
+    fs::create_dir_all(&packDir)?;
+    let packPath=packDir.join(format!("pack-{}.pack",checksum));
+    let indexPath=packPath.with_extension("idx");
+    //先写pack再写index，只有index存在的pack才会被读取
+    for (path,contents) in [(&packPath,&data),(&indexPath,&index)]
+    {
+        let temp=path.with_extension("tmp");
+        fs::write(&temp,contents)?;
+        fs::rename(&temp,path)?;
+    }
+    Ok((packPath,summary))
+}
+
+//所有已经写好index的pack
+pub fn packFiles(root:&Path)->Result<Vec<PathBuf>,GitcError>
+{
+    let mut res=vec![];
+    let packDir=root.join(PACK_DIR);
+    if !packDir.is_dir()
+    {
+        return Ok(res);
+    }
+    for entry in fs::read_dir(packDir)?
+    {
+        let path=entry?.path();
+        if path.extension().is_some_and(|ext| ext=="pack") && path.with_extension("idx").is_file()
+        {
+            res.push(path);
+        }
+    }
+    res.sort();
+    Ok(res)
+}
+
+//读出pack的index
+pub fn readIndex(packPath:&Path)->Result<Vec<PackEntry>,GitcError>
+{
+    let data=fs::read(packPath.with_extension("idx"))?;
+    if data.len()<32 || &data[0..4]!=b"GIDX"
+    {
+        return Err(corrupt("bad index header"));
+    }
+    let count=u32::from_be_bytes([data[8],data[9],data[10],data[11]]) as usize;
+    if data.len()!=12+count*36+20
+    {
+        return Err(corrupt("bad index size"));
+    }
+    let mut entries=Vec::with_capacity(count);
+    for i in 0..count
+    {
+        let start=12+i*36;
+        let mut offset=[0u8;8];
+        offset.copy_from_slice(&data[start+20..start+28]);
+        let mut size=[0u8;8];
+        size.copy_from_slice(&data[start+28..start+36]);
+        entries.push(PackEntry{id:hexId(&data[start..start+20]),offset:u64::from_be_bytes(offset),size:u64::from_be_bytes(size)});
+    }
+    Ok(entries)
+}
+
+//一个pack和它按SHA1排序的index。Objects第一次读取pack时把所有index读进来缓存，之后的查找不再读index文件
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct PackIndex
+{
+    pub path:PathBuf,
+    pub entries:Vec<PackEntry>,
+}
+
+//读出root下所有pack的index
+pub fn loadPacks(root:&Path)->Result<Vec<PackIndex>,GitcError>
+{
+    let mut res=vec![];
+    for path in packFiles(root)?
+    {
+        let entries=readIndex(&path)?;
+        res.push(PackIndex{path,entries});
+    }
+    Ok(res)
+}
+
+//在所有pack中查找对象，返回它所在的pack和偏移
+pub fn findPacked<'a>(packs:&'a [PackIndex],id:&str)->Option<(&'a Path,u64)>
+{
+    for pack in packs.iter()
+    {
+        if let Ok(found)=pack.entries.binary_search_by(|entry| entry.id.as_str().cmp(id))
+        {
+            return Some((&pack.path,pack.entries[found].offset));
+        }
+    }
+    None
+}
+
+//打开pack并定位到offset处的对象，读出它的类型编号、长度和delta的base，之后reader停在压缩的内容之前
+fn openEntry(packPath:&Path,offset:u64)->Result<(BufReader<File>,u8,usize,Option<String>),GitcError>
+{
+    let mut reader=BufReader::new(File::open(packPath)?);
+    reader.seek(SeekFrom::Start(offset))?;
+    let code=readByte(&mut reader,"offset out of range")?;
+    let size=readVarintFrom(&mut reader)?;
+    let mut base=None;
+    if code==KIND_DELTA
+    {
+        let mut raw=[0u8;20];
+        reader.read_exact(&mut raw).map_err(|_| corrupt("truncated base id"))?;
+        base=Some(hexId(&raw));
+    }
+    Ok((reader,code,size,base))
+}
+
+//pack中对象的类型，delta对象沿着base找到不是delta的对象，只读每一项的开头而不解压内容
+pub fn packedKind(packs:&[PackIndex],id:&str)->Result<Option<&'static str>,GitcError>
+{
+    let mut id=id.to_string();
+    for _ in 0..=MAX_DELTA_DEPTH
+    {
+        let Some((packPath,offset))=findPacked(packs,&id) else {return Ok(None)};
+        let (_,code,_,base)=openEntry(packPath,offset)?;
+        match base
+        {
+            Some(base)=>id=base,
+            None=>return Ok(Some(kindName(code).ok_or_else(|| corrupt("unknown entry type"))?)),
+        }
+    }
+    Err(corrupt("delta chain too long"))
+}
+
+//从pack中读出对象的类型和内容，只读取用到的那几项。delta对象沿着base找到不是delta的对象，
+//再把路上的delta依次作用上去，base总是和delta在同一个pack中
+pub fn readPacked(packs:&[PackIndex],id:&str)->Result<Option<(String,Vec<u8>)>,GitcError>
+{
+    let mut deltas=vec![];
+    let mut id=id.to_string();
+    loop
+    {
+        let Some((packPath,offset))=findPacked(packs,&id) else
+        {
+            return if deltas.is_empty() {Ok(None)} else {Err(corrupt("missing delta base"))};
+        };
+        let (reader,code,size,base)=openEntry(packPath,offset)?;
+        let mut contents=Vec::with_capacity(size);
+        ZlibDecoder::new(reader).read_to_end(&mut contents)?;
+        if contents.len()!=size
+        {
+            return Err(corrupt("entry size mismatch"));
+        }
+        match base
+        {
+            Some(_) if deltas.len()>=MAX_DELTA_DEPTH=>return Err(corrupt("delta chain too long")),
+            Some(base)=>
+            {
+                deltas.push(contents);
+                id=base;
+            }
+            None=>
+            {
+                let kind=kindName(code).ok_or_else(|| corrupt("unknown entry type"))?;
+                let mut body=contents;
+                for delta in deltas.iter().rev()
+                {
+                    body=applyDelta(&body,delta)?;
+                }
+                return Ok(Some((kind.to_string(),body)));
+            }
+        }
+    }
+}
+
+//确认新写的pack包含所有ids，之后才能删除旧的pack
+pub fn verifyPack(packPath:&Path,ids:&BTreeSet<String>)->Result<(),GitcError>
+{
+    let entries=readIndex(packPath)?;
+    for id in ids.iter()
+    {
+        if entries.binary_search_by(|entry| entry.id.as_str().cmp(id)).is_err()
+        {
+            return Err(corrupt(&format!("{} is missing {}",packPath.display(),id)));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//一个打开的仓库：工作区的根目录、打开时所在的子目录，以及从.gitc中读出的head和objects。
+//gitMethods中的操作都通过它读写分支、暂存区和对象。打开仓库不会改变当前目录，
+//.gitc中的文件和工作区中的文件都按相对根目录root的"./xxx"记录，读写时拼在root后面，命令行中的路径由normalize转换
+use crate::gitUtils::gitUtils::{HEAD,Objects};
+use crate::gitUtils::gitError::GitcError;
+use crate::gitMethods::gitMethods::{loadState,saveHead};
+
+use std::env;
+use std::path::{Component,Path,PathBuf};
+
+pub const GITC_DIR:&str=".gitc";
+
+pub struct Repository
+{
+    //工作区的根目录，也就是.gitc所在的目录
+    pub root:PathBuf,
+    //打开仓库时的当前目录相对根目录的路径，在根目录(或工作区之外)时为空
+    pub prefix:PathBuf,
+    pub head:HEAD,
+    pub objects:Objects,
+}
+
+//从start开始逐级向上查找含有.gitc文件夹的目录
+pub fn discover(start:&Path)->Result<PathBuf,GitcError>
+{
+    let start=start.canonicalize()?;
+    start.ancestors().find(|dir| dir.join(GITC_DIR).is_dir()).map(Path::to_path_buf).ok_or(GitcError::NotARepository)
+}
+
+impl Repository {
+    //用已有的head和objects组成仓库，根目录为当前目录
+    pub fn new(mut head:HEAD,mut objects:Objects)->Self
+    {
+        let root=env::current_dir().unwrap_or_default();
+        head.index.root=root.clone();
+        objects.root=root.clone();
+        Repository{root,prefix:PathBuf::new(),head,objects}
+    }
+
+    //找到仓库，返回工作区的根目录和start相对它的子目录。
+    //gitDir为None时从start向上查找，否则直接使用这个.gitc文件夹，工作区为它所在的目录
+    pub fn locate(start:&Path,gitDir:Option<&Path>)->Result<(PathBuf,PathBuf),GitcError>
+    {
+        let cwd=start.canonicalize()?;
+        let root=match gitDir
+        {
+            Some(dir)=>
+            {
+                let dir=dir.canonicalize().map_err(|_| GitcError::NotARepository)?;
+                match dir.parent()
+                {
+                    Some(parent) if dir.is_dir() && dir.file_name().is_some_and(|name| name==GITC_DIR)=>parent.to_path_buf(),
+                    _=>return Err(GitcError::NotARepository),
+                }
+            }
+            None=>discover(&cwd)?,
+        };
+        let prefix=cwd.strip_prefix(&root).map(Path::to_path_buf).unwrap_or_default();
+        Ok((root,prefix))
+    }
+
+    //从locate得到的根目录中读出head和objects
+    pub fn load(root:PathBuf,prefix:PathBuf)->Result<Self,GitcError>
+    {
+        let (objects,head)=loadState(&root)?;
+        Ok(Repository{root,prefix,head,objects})
+    }
+
+    //从当前目录开始查找并打开仓库
+    pub fn open(gitDir:Option<&Path>)->Result<Self,GitcError>
+    {
+        let (root,prefix)=Repository::locate(&env::current_dir()?,gitDir)?;
+        Repository::load(root,prefix)
+    }
+
+    //把命令行中相对打开时所在目录的路径(或绝对路径)转换成相对根目录的"./xxx"形式，根目录本身为"."。
+    //路径不必存在，..超出工作区时返回OutsideRepository
+    pub fn normalize(&self,path:&str)->Result<String,GitcError>
+    {
+        let outside=|| GitcError::OutsideRepository(path.to_string());
+        let given=Path::new(path);
+        let relative=if given.is_absolute()
+        {
+            given.strip_prefix(&self.root).map_err(|_| outside())?.to_path_buf()
+        }
+        else {
+            self.prefix.join(given)
+        };
+        let mut parts:Vec<String>=vec![];
+        for component in relative.components()
+        {
+            match component
+            {
+                Component::Normal(name)=>parts.push(name.to_string_lossy().to_string()),
+                Component::ParentDir=>
+                {
+                    parts.pop().ok_or_else(outside)?;
+                }
+                Component::CurDir=>{}
+                Component::RootDir | Component::Prefix(_)=>return Err(outside()),
+            }
+        }
+        if parts.is_empty()
+        {
+            return Ok(String::from("."));
+        }
+        Ok(format!("./{}",parts.join("/")))
+    }
+
+    //保存head和暂存区，对象在写入时已经存进./.gitc/objects
+    pub fn save(&mut self)->Result<(),GitcError>
+    {
+        saveHead(&self.root,&mut self.head)
+    }
+}
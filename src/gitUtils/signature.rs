@@ -0,0 +1,212 @@
+//commit中作者和提交者的身份：名字、邮箱、提交时间和时区，
+//在commit对象中和git一样写成"名字 <邮箱> 秒数 +hhmm"
+use crate::gitUtils::gitError::GitcError;
+
+use serde::{Serialize,Deserialize};
+use std::env;
+use std::time::{SystemTime,UNIX_EPOCH};
+
+#[derive(Debug,Clone,Default,PartialEq,Eq,Serialize,Deserialize)]
+pub struct Signature
+{
+    pub name:String,
+    pub email:String,
+    //UNIX时间戳，单位为秒
+    pub time:i64,
+    //相对UTC的时区，单位为分钟，东八区为480
+    pub offset:i32,
+}
+
+const WEEKDAYS:[&str;7]=["Sun","Mon","Tue","Wed","Thu","Fri","Sat"];
+const MONTHS:[&str;12]=["Jan","Feb","Mar","Apr","May","Jun","Jul","Aug","Sep","Oct","Nov","Dec"];
+
+//公历日期到1970-01-01之后的天数
+fn daysFromCivil(year:i64,month:i64,day:i64)->i64
+{
+    let year=if month<=2 {year-1} else {year};
+    let era=year.div_euclid(400);
+    let yoe=year-era*400;
+    let doy=(153*((month+9)%12)+2)/5+day-1;
+    let doe=yoe*365+yoe/4-yoe/100+doy;
+    era*146097+doe-719468
+}
+
+//1970-01-01之后的天数到公历的年、月、日
+fn civilFromDays(days:i64)->(i64,i64,i64)
+{
+    let days=days+719468;
+    let era=days.div_euclid(146097);
+    let doe=days-era*146097;
+    let yoe=(doe-doe/1460+doe/36524-doe/146096)/365;
+    let doy=doe-(365*yoe+yoe/4-yoe/100);
+    let mp=(5*doy+2)/153;
+    let day=doy-(153*mp+2)/5+1;
+    let month=if mp<10 {mp+3} else {mp-9};
+    (if month<=2 {yoe+era*400+1} else {yoe+era*400},month,day)
+}
+
+//"+0800"、"-0130"这样的时区
+pub fn parseOffset(value:&str)->Option<i32>
+{
+    let (sign,digits)=match value.split_at_checked(1)?
+    {
+        ("+",digits)=>(1,digits),
+        ("-",digits)=>(-1,digits),
+        _=>return None,
+    };
+    if digits.len()!=4 || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let hours:i32=digits[..2].parse().ok()?;
+    let minutes:i32=digits[2..].parse().ok()?;
+    if minutes>=60
+    {
+        return None;
+    }
+    Some(sign*(hours*60+minutes))
+}
+
+pub fn formatOffset(offset:i32)->String
+{
+    let sign=if offset<0 {'-'} else {'+'};
+    format!("{}{:02}{:02}",sign,offset.abs()/60,offset.abs()%60)
+}
+
+//解析时间，返回时间戳和时区。支持git内部的"秒数 +hhmm"、"@秒数"，
+//以及"YYYY-MM-DD"、"YYYY-MM-DD HH:MM:SS"、"YYYY-MM-DDTHH:MM:SS"，后两种可以带" +hhmm"，不带时区时按UTC
+pub fn parseDate(value:&str)->Option<(i64,i32)>
+{
+    let value=value.trim();
+    let (rest,offset)=match value.rsplit_once(' ').and_then(|(rest,zone)| Some((rest,parseOffset(zone)?)))
+    {
+        Some((rest,offset))=>(rest.trim(),offset),
+        None=>(value,0),
+    };
+    if let Some(seconds)=rest.strip_prefix('@')
+    {
+        return Some((seconds.parse().ok()?,offset));
+    }
+    if !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+    {
+        return Some((rest.parse().ok()?,offset));
+    }
+    let (date,clock)=match rest.split_once([' ','T'])
+    {
+        Some((date,clock))=>(date,clock),
+        None=>(rest,"00:00:00"),
+    };
+    let date:Vec<i64>=date.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let clock:Vec<i64>=clock.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let (&[year,month,day],&[hour,minute,second])=(date.as_slice(),clock.as_slice()) else {return None};
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour>23 || minute>59 || second>60
+    {
+        return None;
+    }
+    let local=daysFromCivil(year,month,day)*86400+hour*3600+minute*60+second;
+    Some((local-offset as i64*60,offset))
+}
+
+//和git一样去掉名字和邮箱中的换行和尖括号，并去掉首尾的空白和标点，
+//否则它们会在commit对象中多出一行header，或者读回来时解析不了
+fn withoutCrud(value:&str)->String
+{
+    let isCrud=|c:char| c.is_ascii_whitespace() || ".,:;<>\"\\'".contains(c);
+    value.chars().filter(|c| !matches!(c,'\n'|'<'|'>')).collect::<String>().trim_matches(isCrud).to_string()
+}
+
+//当前时间，时区按UTC
+fn now()->i64
+{
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0,|time| time.as_secs() as i64)
+}
+
+impl Signature {
+    pub fn new(name:String,email:String,time:i64,offset:i32)->Self
+    {
+        Signature{name,email,time,offset}
+    }
+
+    //解析"名字 <邮箱>"，没有尖括号时整个都是名字，邮箱为空
+    pub fn parse(ident:&str)->Self
+    {
+        let mut res=Signature::default();
+        match ident.split_once('<')
+        {
+            Some((name,email))=>
+            {
+                res.name=withoutCrud(name);
+                res.email=withoutCrud(email);
+            }
+            None=>res.name=withoutCrud(ident),
+        }
+        res
+    }
+
+    //按环境变量GITC_<ROLE>_NAME、GITC_<ROLE>_EMAIL、GITC_<ROLE>_DATE覆盖名字、邮箱和时间，
+    //没有设置GITC_<ROLE>_DATE时使用当前时间。role为AUTHOR或COMMITTER
+    fn fromEnv(mut self,role:&str)->Result<Self,GitcError>
+    {
+        if let Ok(name)=env::var(format!("GITC_{}_NAME",role))
+        {
+            self.name=withoutCrud(&name);
+        }
+        if let Ok(email)=env::var(format!("GITC_{}_EMAIL",role))
+        {
+            self.email=withoutCrud(&email);
+        }
+        (self.time,self.offset)=match env::var(format!("GITC_{}_DATE",role))
+        {
+            Ok(date)=>parseDate(&date).ok_or(GitcError::InvalidDate(date))?,
+            Err(_)=>(now(),0),
+        };
+        Ok(self)
+    }
+
+    //作者：ident给出的"名字 <邮箱>"，可以被GITC_AUTHOR_*覆盖
+    pub fn author(ident:&str)->Result<Self,GitcError>
+    {
+        Signature::parse(ident).fromEnv("AUTHOR")
+    }
+
+    //提交者：默认和作者是同一个人，可以被GITC_COMMITTER_*覆盖
+    pub fn committer(author:&Signature)->Result<Self,GitcError>
+    {
+        Signature::new(author.name.clone(),author.email.clone(),0,0).fromEnv("COMMITTER")
+    }
+
+    //"名字 <邮箱>"
+    pub fn ident(&self)->String
+    {
+        format!("{} <{}>",self.name,self.email)
+    }
+
+    //commit对象中的格式"名字 <邮箱> 秒数 +hhmm"
+    pub fn encode(&self)->String
+    {
+        format!("{} {} {}",self.ident(),self.time,formatOffset(self.offset))
+    }
+
+    pub fn decode(line:&str)->Result<Self,GitcError>
+    {
+        let corrupt=|| GitcError::CorruptStore(format!("bad signature '{}'",line));
+        let (ident,date)=line.rsplit_once('>').ok_or_else(corrupt)?;
+        let mut res=Signature::parse(&format!("{}>",ident));
+        let (time,offset)=date.trim().split_once(' ').ok_or_else(corrupt)?;
+        res.time=time.parse().map_err(|_| corrupt())?;
+        res.offset=parseOffset(offset).ok_or_else(corrupt)?;
+        Ok(res)
+    }
+
+    //log中显示的时间，按它自己的时区，例如"Tue Nov 14 22:13:20 2023 +0000"
+    pub fn date(&self)->String
+    {
+        let local=self.time+self.offset as i64*60;
+        let days=local.div_euclid(86400);
+        let seconds=local.rem_euclid(86400);
+        let (year,month,day)=civilFromDays(days);
+        format!("{} {} {} {:02}:{:02}:{:02} {} {}",
+            WEEKDAYS[(days+4).rem_euclid(7) as usize],MONTHS[(month-1) as usize],day,
+            seconds/3600,seconds%3600/60,seconds%60,year,formatOffset(self.offset))
+    }
+}
@@ -0,0 +1,159 @@
+use crate::gitUtils::lineDiff::{splitLines,myers,changedRegions};
+
+//一个文件三方合并后的结果，conflicts为冲突块的数量
+pub struct Merge3
+{
+    pub contents:Vec<u8>,
+    pub conflicts:usize,
+}
+
+//冲突块的写法：Merge只写出两边的内容，Diff3在中间用|||||||再写出base中的内容
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum ConflictStyle
+{
+    #[default]
+    Merge,
+    Diff3,
+}
+
+impl ConflictStyle {
+    pub fn fromName(name:&str)->Option<ConflictStyle>
+    {
+        match name
+        {
+            "merge"=>Some(ConflictStyle::Merge),
+            "diff3"=>Some(ConflictStyle::Diff3),
+            _=>None,
+        }
+    }
+}
+
+//一个改动区间，base[baseStart..baseEnd]被替换成了side[sideStart..sideEnd]
+#[derive(Clone,Copy)]
+struct Hunk
+{
+    baseStart:usize,
+    baseEnd:usize,
+    sideStart:usize,
+    sideEnd:usize,
+}
+
+fn hunks(base:&[&[u8]],side:&[&[u8]])->Vec<Hunk>
+{
+    changedRegions(&myers(base,side)).into_iter().map(|(baseStart,baseEnd,sideStart,sideEnd)| Hunk{baseStart,baseEnd,sideStart,sideEnd}).collect()
+}
+
+//base[start..end]这一段在side中对应的行，hunks为落在这一段中的改动
+fn sideRange(start:usize,end:usize,hunks:&[Hunk],delta:isize)->(usize,usize)
+{
+    match (hunks.first(),hunks.last())
+    {
+        (Some(first),Some(last))=>(first.sideStart-(first.baseStart-start),last.sideEnd+(end-last.baseEnd)),
+        _=>((start as isize+delta) as usize,(end as isize+delta) as usize),
+    }
+}
+
+fn pushLines(out:&mut Vec<u8>,lines:&[&[u8]])
+{
+    for line in lines
+    {
+        out.extend_from_slice(line);
+    }
+}
+
+fn pushMarker(out:&mut Vec<u8>,marker:&str,label:&str)
+{
+    if !out.is_empty() && !out.ends_with(b"\n")
+    {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(marker.as_bytes());
+    if !label.is_empty()
+    {
+        out.push(b' ');
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(b'\n');
+}
+
+//以base为共同祖先合并ours和theirs，两边互不重叠的改动自动合并，
+//重叠(或紧挨着)的改动写成<<<<<<< / ======= / >>>>>>>冲突块，style为Diff3时在=======之前加上||||||| base
+pub fn merge3(base:&[u8],ours:&[u8],theirs:&[u8],oursLabel:&str,theirsLabel:&str,style:ConflictStyle)->Merge3
+{
+    let baseLines=splitLines(base);
+    let oursLines=splitLines(ours);
+    let theirsLines=splitLines(theirs);
+    let oursHunks=hunks(&baseLines,&oursLines);
+    let theirsHunks=hunks(&baseLines,&theirsLines);
+
+    let mut contents:Vec<u8>=vec![];
+    let mut conflicts=0;
+    let mut basePos=0;
+    //两边在当前位置之前累计的行数差
+    let mut oursDelta:isize=0;
+    let mut theirsDelta:isize=0;
+    let mut i=0;
+    let mut j=0;
+    while i<oursHunks.len() || j<theirsHunks.len()
+    {
+        //从最靠前的改动开始，把和它重叠的改动都并进同一组
+        let takeOurs=j>=theirsHunks.len() || (i<oursHunks.len() && oursHunks[i].baseStart<=theirsHunks[j].baseStart);
+        let first=if takeOurs {oursHunks[i]} else {theirsHunks[j]};
+        let start=first.baseStart;
+        let mut end=first.baseEnd;
+        let oursFrom=i;
+        let theirsFrom=j;
+        loop
+        {
+            if i<oursHunks.len() && oursHunks[i].baseStart<=end
+            {
+                end=end.max(oursHunks[i].baseEnd);
+                i+=1;
+            }
+            else if j<theirsHunks.len() && theirsHunks[j].baseStart<=end
+            {
+                end=end.max(theirsHunks[j].baseEnd);
+                j+=1;
+            }
+            else
+            {
+                break;
+            }
+        }
+        pushLines(&mut contents,&baseLines[basePos..start]);
+
+        let groupOurs=&oursHunks[oursFrom..i];
+        let groupTheirs=&theirsHunks[theirsFrom..j];
+        let (oursStart,oursEnd)=sideRange(start,end,groupOurs,oursDelta);
+        let (theirsStart,theirsEnd)=sideRange(start,end,groupTheirs,theirsDelta);
+        let oursPart=&oursLines[oursStart..oursEnd];
+        let theirsPart=&theirsLines[theirsStart..theirsEnd];
+        if groupTheirs.is_empty() || oursPart==theirsPart
+        {
+            pushLines(&mut contents,oursPart);
+        }
+        else if groupOurs.is_empty()
+        {
+            pushLines(&mut contents,theirsPart);
+        }
+        else
+        {
+            conflicts+=1;
+            pushMarker(&mut contents,"<<<<<<<",oursLabel);
+            pushLines(&mut contents,oursPart);
+            if style==ConflictStyle::Diff3
+            {
+                pushMarker(&mut contents,"|||||||","base");
+                pushLines(&mut contents,&baseLines[start..end]);
+            }
+            pushMarker(&mut contents,"=======","");
+            pushLines(&mut contents,theirsPart);
+            pushMarker(&mut contents,">>>>>>>",theirsLabel);
+        }
+        oursDelta=oursEnd as isize-end as isize;
+        theirsDelta=theirsEnd as isize-end as isize;
+        basePos=end;
+    }
+    pushLines(&mut contents,&baseLines[basePos..]);
+    Merge3{contents,conflicts}
+}
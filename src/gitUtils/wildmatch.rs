@@ -0,0 +1,89 @@
+//和git相同的通配符匹配：*、?、[a-z]、[!abc]以及**。
+//pathname为true时*和?不匹配'/'，只有**可以跨过多层文件夹，"**/"还可以匹配0层文件夹
+pub fn wildmatch(pattern:&str,text:&str,pathname:bool)->bool
+{
+    let pattern:Vec<char>=pattern.chars().collect();
+    let text:Vec<char>=text.chars().collect();
+    matchFrom(&pattern,&text,pathname)
+}
+
+//pattern中是否有通配符
+pub fn isGlob(pattern:&str)->bool
+{
+    pattern.contains(['*','?','['])
+}
+
+fn matchFrom(pattern:&[char],text:&[char],pathname:bool)->bool
+{
+    match pattern.first()
+    {
+        None=>text.is_empty(),
+        Some('*') if pattern.get(1)==Some(&'*')=>
+        {
+            let rest=&pattern[2..];
+            if rest.first()==Some(&'/') && matchFrom(&rest[1..],text,pathname)
+            {
+                return true;
+            }
+            (0..=text.len()).any(|i| matchFrom(rest,&text[i..],pathname))
+        }
+        Some('*')=>
+        {
+            for i in 0..=text.len()
+            {
+                if matchFrom(&pattern[1..],&text[i..],pathname)
+                {
+                    return true;
+                }
+                if i<text.len() && pathname && text[i]=='/'
+                {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?')=>match text.first()
+        {
+            Some('/') if pathname=>false,
+            Some(_)=>matchFrom(&pattern[1..],&text[1..],pathname),
+            None=>false,
+        },
+        Some('[')=>match (classMatches(&pattern[1..],text.first().copied()),text.first())
+        {
+            (Some((true,len)),Some(c)) if !(pathname && *c=='/')=>matchFrom(&pattern[len+1..],&text[1..],pathname),
+            (Some(_),_)=>false,
+            //没有对应的']'时'['就是普通字符
+            (None,_)=>text.first()==Some(&'[') && matchFrom(&pattern[1..],&text[1..],pathname),
+        },
+        Some('\\') if pattern.len()>1=>text.first()==Some(&pattern[1]) && matchFrom(&pattern[2..],&text[1..],pathname),
+        Some(c)=>text.first()==Some(c) && matchFrom(&pattern[1..],&text[1..],pathname),
+    }
+}
+
+//匹配'['之后的字符集，返回是否匹配以及字符集(包括']')的长度
+fn classMatches(class:&[char],c:Option<char>)->Option<(bool,usize)>
+{
+    let negated=matches!(class.first(),Some('!') | Some('^'));
+    let mut i=if negated {1} else {0};
+    let mut found=false;
+    let mut first=true;
+    while i<class.len()
+    {
+        if class[i]==']' && !first
+        {
+            return Some((found!=negated && c.is_some(),i+1));
+        }
+        first=false;
+        if i+2<class.len() && class[i+1]=='-' && class[i+2]!=']'
+        {
+            found|=c.is_some_and(|c| class[i]<=c && c<=class[i+2]);
+            i+=3;
+        }
+        else
+        {
+            found|=c==Some(class[i]);
+            i+=1;
+        }
+    }
+    None
+}
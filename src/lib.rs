@@ -0,0 +1,7 @@
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![allow(clippy::module_inception)]
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod gitUtils;
+pub mod gitMethods;
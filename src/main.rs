@@ -1,720 +1,666 @@
-mod gitUtils;
-
-use gitUtils::gitUtils::References;
-use gitUtils::gitUtils::HEAD;
-use gitUtils::gitUtils::Branch;
-use gitUtils::gitUtils::blob;
-use gitUtils::gitUtils::Objects;
-use gitUtils::gitUtils::tree;
-use gitUtils::gitUtils::Commit;
-use gitUtils::gitUtils::objecttype;
-
-mod gitMethods;
-use gitMethods::gitMethods::*;
-
-use serde::{Serialize, Deserialize};
-use bincode::{serialize, deserialize};
-use std::io::{Bytes, Read, Write};
-use std::rc::Rc;
-use std::cell::RefCell;
-use std::fs;
-use std::fs::File;
-use std::collections::HashMap;
-use std::path::Path;
-use hex_literal::hex;
-use sha1::{Sha1, Digest};
-use walkdir::WalkDir;
-
-/*const Hex:[char;16]=['0','1','2','3','4','5','6','7','8','9','a','b','c','d','e','f'];
-
-pub fn fatherName(path:&str)->String
+#![allow(non_snake_case)]
+
+use gitc::gitMethods::gitMethods::*;
+use gitc::gitMethods::gitLog::{gitLog,LogOptions};
+use gitc::gitMethods::gitStatus::gitStatus;
+#[cfg(test)]
+use gitc::gitMethods::gitStatus::{statusEntries,FileStatus};
+#[cfg(test)]
+use gitc::gitMethods::gitDiff::diffText;
+#[cfg(test)]
+use gitc::gitMethods::gitLog::logLines;
+#[cfg(test)]
+use gitc::gitUtils::lineDiff::{diff,unifiedHunks,splitLines,DiffOp};
+use gitc::gitMethods::gitMergeBase::gitMergeBase;
+use gitc::gitMethods::gitRevParse::gitRevParse;
+use gitc::gitMethods::gitDiff::{gitDiff,DiffMode,DiffOptions};
+use gitc::gitUtils::lineDiff::DiffAlgorithm;
+use gitc::gitUtils::objectStore::storeStats;
+use gitc::gitMethods::gitRepack::gitRepack;
+use gitc::gitMethods::gitGc::{gitGc,parsePrune,pruneExpire};
+use gitc::gitMethods::gitConfig::{gitConfig,ConfigAction};
+use gitc::gitUtils::config::{Config,ConfigScope};
+use gitc::gitMethods::gitIgnore::{gitClean,gitCheckIgnore,CleanOptions};
+use gitc::gitUtils::signature::parseDate;
+use gitc::gitUtils::gitError::GitcError;
+use gitc::gitUtils::repository::Repository;
+
+use std::env;
+use std::error::Error;
+use std::path::{Path,PathBuf};
+use std::process;
+
+#[cfg(test)]
+use {
+    gitc::gitUtils::threeWay::ConflictStyle,
+    gitc::gitUtils::gitUtils::{HEAD,Branch,Objects,References,Commit},
+    gitc::gitUtils::threeWay::merge3,
+    gitc::gitUtils::legacy::{LegacyHEAD,LegacyBranch,LegacyCommit,LegacyTree,LegacyObjects},
+    bincode::deserialize,
+    std::io::Read,
+    std::rc::Rc,
+    std::cell::RefCell,
+    std::fs::File,
+    std::collections::BTreeMap,
+};
+
+const USAGE:&str="usage: gitc [-C <path>] [--git-dir=<path>] <command> [<args>]
+
+gitc looks for .gitc in the current directory and its parents, so it can be run from any subdirectory.
+   -C <path>                     Run as if gitc was started in <path>
+   --git-dir=<path>              Use the repository at <path> (a .gitc directory) instead of searching for one
+
+These are the gitc commands:
+   init                          Create an empty gitc repository in ./.gitc
+   add [-A | -u] [<pathspec>...] Add file contents to the index
+   rm [--cached] [-r] [-f] <path>...
+                                 Remove files from the index and the working tree
+   commit -m <msg> [--author '<name> <email>']
+                                 Record the staged snapshot as a new commit
+   branch [<name>]               List branches, or create a new branch
+   checkout [-f | --force] <branch>
+                                 Switch to another branch and update the working tree
+   merge <branch> [-m <msg>] [--author '<name> <email>'] [--no-ff | --ff-only]
+                                 Merge another branch into the current branch
+   merge-base [--all] <commit> <commit>
+                                 Find the best common ancestor(s) of two commits
+   rev-parse <revision>          Show the full SHA1 of a commit, e.g. HEAD~2, master^2, v1 or an abbreviated SHA1
+   diff [--cached] [-U<n>] [--patience | --histogram] [<commit> <commit>]
+                                 Show changes between the working tree, the index and commits
+   log [--oneline] [--graph] [-n <n>] [--author=<pattern>] [--grep=<pattern>] [--since=<date>] [--until=<date>] [<commit>] [-- <path>]
+                                 Show the commit history of the current branch
+   status [--porcelain | -s]     Show the working tree status
+   clean (-n | -f) [-d] [-x | -X]
+                                 Remove untracked files from the working tree
+   check-ignore [-v] <path>...   Show which paths are ignored by .gitcignore rules
+   count-objects                 Show the number of stored objects and their compressed size
+   repack                        Pack all objects into one pack file, storing similar objects as deltas
+   gc [--prune=<when>]           Remove objects that are not reachable from any branch, tag, reflog or the index
+   config [--global | --local] (get <key> | set <key> <value> | unset <key> | --list)
+                                 Read or change the repository (.gitc/config) or user (~/.gitcconfig) configuration
+   help                          Show this message
+
+GITC_AUTHOR_NAME, GITC_AUTHOR_EMAIL and GITC_AUTHOR_DATE (and the GITC_COMMITTER_* equivalents)
+override the identities and dates recorded in new commits, e.g. GITC_AUTHOR_DATE='1700000000 +0800'";
+
+//命令之前的全局选项
+struct GlobalOptions
 {
-    let p=Path::new(path);
-    let mut fileName=path.to_string();
-    let namearray=fileName.split("/");
-    let arraysize=namearray.clone().count();
-    let mut c:usize=1;
-    let mut pname=String::new();
-    if arraysize==1
-    {
-        return String::from("");
-    }
-    else if arraysize==2
-    {
-        return String::from(".");
-    }
-    else
+    //-C <path>：命令就像在path中运行一样(可以给出多次，后一个相对前一个)，没有时为当前目录
+    start:PathBuf,
+    //--git-dir=<path>：直接指定.gitc文件夹，相对start
+    gitDir:Option<PathBuf>,
+    //命令所在的位置
+    command:usize,
+}
+
+//不切换当前目录，-C只决定从哪里查找仓库以及命令行中的路径相对哪个目录
+fn globalOptions(args:&[String])->Result<GlobalOptions,Box<dyn Error>>
+{
+    let mut start=env::current_dir()?;
+    let mut gitDir=None;
+    let mut i=0;
+    while let Some(arg)=args.get(i)
     {
-        for s in namearray
+        if arg=="-C" || arg=="--git-dir"
         {
-            if c==arraysize
+            let value=args.get(i+1).ok_or_else(|| format!("option '{}' requires a value",arg))?;
+            if arg=="-C"
             {
-                break;
+                start=start.join(value);
+                if !start.is_dir()
+                {
+                    return Err(format!("cannot change to '{}': not a directory",value).into());
+                }
             }
-            if c>1
-            {
-                pname.push_str("/");
+            else {
+                gitDir=Some(PathBuf::from(value));
             }
-            pname.push_str(s);
-            c=c+1;
+            i+=2;
         }
-        return pname;
-    }
-}
-pub fn gitMerge(head:&mut HEAD,branch1:String,branch2:String,message:&str,author:&str,objects:&mut Objects)
-{
-
-    let mut reference=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-    let mut mainbranch=head.branch.get(&branch1).cloned().unwrap();
-    let mut minorbranch=head.branch.get(&branch2).cloned().unwrap();
-
-    let mut maincommit=mainbranch.borrow().commitpointer.clone().unwrap();
-    let mut minorcommit=minorbranch.borrow().commitpointer.clone().unwrap();
-
-    let mut newcommit=Commit::new();
-    newcommit.t=objecttype::commit;
-    newcommit.message=String::from(message);
-    newcommit.author=String::from(author);
-    for c in 0..maincommit.borrow().parents.len()
-    {
-        if let Some(son)=maincommit.borrow().parents.get(c).cloned()
+        else if let Some(value)=arg.strip_prefix("--git-dir=")
         {
-            newcommit.parents.push(son);
+            gitDir=Some(PathBuf::from(value));
+            i+=1;
         }
-    }
-    newcommit.parents.push(Some(maincommit.clone()));
-    for c in 0..minorcommit.borrow().parents.len()
-    {
-        if let Some(son)=minorcommit.borrow().parents.get(c).cloned()
-        {
-            newcommit.mergeparents.push(son);
+        else {
+            break;
         }
     }
-    newcommit.mergeparents.push(Some(minorcommit.clone()));
-    newcommit.snapshot=maincommit.borrow().snapshot.clone();
+    let gitDir=gitDir.map(|dir| start.join(dir));
+    Ok(GlobalOptions{start,gitDir,command:i})
+}
 
-    let mut whilecount:usize=0;
-    let mut mainCurrentTreeNode=newcommit.snapshot.clone();
-    let mut minorCurrentTreeNode=minorcommit.borrow_mut().snapshot.clone();
-    let mut mainTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    mainTreeQueue.push(mainCurrentTreeNode.clone());
-    let mut minorTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    minorTreeQueue.push(minorCurrentTreeNode.clone());
-    while minorTreeQueue.len()!=0
+//取出"-m <msg>"或"--author=<name>"这类选项的值
+fn optionValue(args:&[String],short:&str,long:&str)->Result<Option<String>,Box<dyn Error>>
+{
+    let mut value=None;
+    let mut i=0;
+    while i<args.len()
     {
-        if whilecount==0
-        {
-            mainTreeQueue.remove(0);
-            minorTreeQueue.remove(0);
-        }
-        else if whilecount>0
+        let arg=&args[i];
+        if arg==short || arg==long
         {
-            mainCurrentTreeNode=mainTreeQueue.get(0).cloned().unwrap();
-            minorCurrentTreeNode=minorTreeQueue.get(0).cloned().unwrap();
-            mainTreeQueue.remove(0);
-            minorTreeQueue.remove(0);
-        }
-        for (key,value) in minorCurrentTreeNode.borrow().blobs.clone()
-        {
-            //println!("{:?},{:?}",key,value);
-            if(mainCurrentTreeNode.borrow().blobs.contains_key(&key))  //也有这个文件 涉及合并问题 之后再搞
-            {
-
-            }
-            else  //main branch 中没有 直接加入
+            match args.get(i+1)
             {
-
-                mainCurrentTreeNode.borrow_mut().blobs.insert(key.clone(),value.clone());
-                let SHA1id_temp=getSHA1(&serialize(&value.clone()).unwrap());
-                objects.blobmap.insert(SHA1id_temp.clone(),value.clone());
-                reference.borrow_mut().refermap.insert(key.clone(),SHA1id_temp.clone());
+                Some(v)=>value=Some(v.clone()),
+                None=>return Err(format!("option '{}' requires a value",arg).into()),
             }
+            i+=1;
         }
-        for (key,value) in minorCurrentTreeNode.borrow().trees.clone()
+        else if let Some(v)=arg.strip_prefix(&format!("{}=",long))
         {
-            if(mainCurrentTreeNode.borrow().trees.contains_key(&key))  //都有这个文件夹就进去
-            {
-                minorTreeQueue.push(value.clone());
-                mainTreeQueue.push(mainCurrentTreeNode.borrow().trees.get(&key).cloned().unwrap());
-            }
-            else
-            {
-                mainCurrentTreeNode.borrow_mut().trees.insert(key,value);
-            }
+            value=Some(v.to_string());
         }
-        whilecount+=1;
+        i+=1;
     }
+    Ok(value)
+}
 
-    //对所有文件夹的 SHA进行一次更新
-    let mut renewcount:usize=0;
-    let mut renewCurrentTreeNode=newcommit.snapshot.clone();
-    let mut renewTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    let mut recordTreeQueue:Vec<Rc<RefCell<tree>>>=vec![];
-    renewTreeQueue.push(renewCurrentTreeNode.clone());
-    recordTreeQueue.push(renewCurrentTreeNode.clone());
-
-    while renewTreeQueue.len()!=0
+//去掉选项后剩下的位置参数
+fn positionals(args:&[String],valued:&[&str])->Vec<String>
+{
+    let mut res=vec![];
+    let mut i=0;
+    while i<args.len()
     {
-        if renewcount==0
-        {
-            renewTreeQueue.remove(0);
-        }
-        else if renewcount>0
+        let arg=&args[i];
+        if valued.contains(&arg.as_str())
         {
-            renewCurrentTreeNode=renewTreeQueue.get(0).cloned().unwrap();
-            renewTreeQueue.remove(0);
+            i+=1;
         }
-        for (key,value) in renewCurrentTreeNode.borrow().trees.clone()
+        else if !arg.starts_with('-') || arg=="-"
         {
-            recordTreeQueue.push(value.clone());
+            res.push(arg.clone());
         }
-        renewcount+=1;
+        i+=1;
     }
-    for index in 0..recordTreeQueue.len()
-    {
-        let obj=recordTreeQueue.get(index).cloned().unwrap();
-        let SHA1id_temp=getSHA1(&serialize(&obj).unwrap());
-        objects.treemap.insert(SHA1id_temp.clone(),obj.clone());
-        reference.borrow_mut().refermap.insert(obj.clone().borrow().name.clone(),SHA1id_temp.clone());
-    }
-
-
-    let mut commit_rc=Rc::new(RefCell::new(newcommit));
-    let SHA1id_temp=getSHA1(&serialize(&commit_rc).unwrap());
-    objects.commitmap.insert(SHA1id_temp.clone(),commit_rc.clone());
-    reference.borrow_mut().refermap.insert(String::from(message),SHA1id_temp.clone());
-
-
-    head.branch.get(&head.currentBranchName.clone()).cloned().unwrap().borrow_mut().commitpointer=Some(commit_rc.clone());
-
+    res
 }
-pub fn gitCommit(head:&mut HEAD,objects:&mut Objects,  message:&str, author:&str)
-{
-    let mut reference=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-    let mut commit=Commit::new();
-    commit.message=String::from(message);
-    commit.author=String::from(author);
-
-    let snapshot=load_reference_tree(&reference,objects,String::from("."));
-
-    commit.snapshot=snapshot;
-
-    let currentbranch=head.branch.get(&head.currentBranchName.clone()).cloned().unwrap();
-
-    let fathercommit=currentbranch.borrow().commitpointer.clone().unwrap();
-    if Some(fathercommit.clone()).is_some()
-    {
-        for c in 0..fathercommit.borrow().parents.len()
-        {
-            if let Some(son)=fathercommit.borrow().parents.get(c).cloned()
-            {
-                commit.parents.push(son);
-            }
-        }
-        commit.parents.push(Some(fathercommit));
-    }
-
-    let mut commit_rc=Rc::new(RefCell::new(commit));
-
-    let SHA1id_temp=getSHA1(&serialize(&commit_rc).unwrap());
-    objects.commitmap.insert(SHA1id_temp.clone(),commit_rc.clone());
-    reference.borrow_mut().refermap.insert(String::from(message),SHA1id_temp.clone());
-
-    let mut branchname=head.currentBranchName.clone();
-
-    head.branch.get(&branchname).cloned().unwrap().borrow_mut().commitpointer=Some(commit_rc.clone());
-
-
-/*    let mut f_obj=File::create("./.gitc/maps/objs.txt");
-    f_obj.unwrap().write(&serialize(&objects).unwrap());
-    let mut f_ref=File::create("./.gitc/maps/refs.txt");
-    f_ref.unwrap().write(&serialize(&reference).unwrap());
-    let mut h_obj=File::create("./.gitc/maps/head.txt");
-    h_obj.unwrap().write(&serialize(&head).unwrap());*/
-
-
 
+fn defaultAuthor()->String
+{
+    std::env::var("USER").unwrap_or_else(|_| String::from("unknown"))
 }
 
-pub fn gitAdd(path:String,objects:&mut Objects,head:&mut HEAD)
-{
-     let mut reference= head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
-
-
-     let p=Path::new(&(path));
-     if p.is_file()
-     {
-         let mut file=blob::new(path.clone());
-         file.getContents(path.clone());
-         let file_rc=Rc::new(RefCell::new(file));
-         let SHA1id=getSHA1(&serialize(&file_rc).unwrap());
-         if objects.blobmap.contains_key(&SHA1id)
-         {
-             return;
-         }
-         else
-         {
-             objects.blobmap.insert(SHA1id.clone(),file_rc.clone());
-             reference.borrow_mut().refermap.insert(path.clone(),SHA1id.clone());
-             let part1=&SHA1id[0..2];
-             let part2=&SHA1id[2..40];
-             let mut dir_path="./.gitc/objects/".to_string();
-             let path1=dir_path+&String::from(part1);
-             let dir=Path::new(&path1);
-             if dir.exists()==false
-             {
-                 fs::create_dir(&path1);
-             }
-             let path_temp=path1+&String::from("/");
-             let path2=path_temp+&String::from(part2);
-             let mut f=File::create(&path2);
-             f.unwrap().write(&serialize(&file_rc).unwrap());
-             let mut fathername=fatherName(&(path));
-             let mut sonname=path.clone();
-             let mut sonpath=p.clone();
-             let mut sonobj=Rc::new(RefCell::new(tree::new(String::from(""))));
-             while !fathername.eq("")
-             {
-                 let mut tree_temp_rc=Rc::new(RefCell::new(tree::new(fathername.clone())));
-
-                 if reference.borrow_mut().refermap.contains_key(&fathername)
-                 {
-                     let mut temp=load_reference_tree(&reference,objects,fathername.clone());
-                     for (key,value) in temp.borrow().blobs.clone()
-                     {
-                         tree_temp_rc.borrow_mut().blobs.insert(key,value);
-                     }
-                     for (key,value) in temp.borrow().trees.clone()
-                     {
-                         tree_temp_rc.borrow_mut().trees.insert(key,value);
-                     }
-                 }
-
-                 //println!("{:?}",tree_temp_rc.borrow().blobs);
-                 if sonpath.clone().is_file()
-                 {
-                     tree_temp_rc.borrow_mut().blobs.insert(sonname.clone(),file_rc.clone());
-                 }
-                 else
-                 {
-                     tree_temp_rc.borrow_mut().trees.insert(sonname.clone(),sonobj.clone());
-
-                 }
-                 //println!("{:?}",tree_temp_rc.borrow().blobs);
-                 sonname=fathername.clone();
-                 sonpath=Path::new(&sonname);
-                 fathername=fatherName(&(sonname));
-                 sonobj=tree_temp_rc.clone();
-                 let SHA1id_temp=getSHA1(&serialize(&sonobj).unwrap());
-                 objects.treemap.insert(SHA1id_temp.clone(),sonobj.clone());
-                 reference.borrow_mut().refermap.insert(sonname.clone(),SHA1id_temp.clone());
-             }
-/*             let mut f_obj=File::create("./.gitc/maps/objs.txt");
-             f_obj.unwrap().write(&serialize(&objects).unwrap());
-
-             let mut f_ref=File::create("./.gitc/maps/refs.txt");
-             f_ref.unwrap().write(&serialize(&reference).unwrap());*/
-             //println!("{:?}",objects);
-             //println!("{:?}",reference);
-         }
-         //println!("{:?}",reference.borrow().refermap.clone());
-     }
-    else
-    {
-        return;
-    }
-
-}
-pub fn gitRm(path:String,objects:&mut Objects,head:&mut HEAD)
+//--since、--until的时间
+fn logDate(value:&str)->Result<i64,GitcError>
 {
-    let mut reference=head.branch.get(&head.currentBranchName).cloned().unwrap().borrow_mut().references.clone();
+    parseDate(value).map(|(time,_)| time).ok_or_else(|| GitcError::InvalidDate(value.to_string()))
+}
 
-    let p=Path::new(&(path));
-    if p.is_file()
+const COMMANDS:[&str;21]=["init","add","rm","commit","branch","checkout","merge","merge-base","rev-parse","diff","log","status",
+    "clean","check-ignore","count-objects","repack","gc","config","help","--help","-h"];
+
+//命令不是gitc自带的命令时按alias.<name>展开，展开后还是别名时继续展开
+fn expandAlias(config:&Config,args:&[String])->Result<Vec<String>,Box<dyn Error>>
+{
+    let mut args=args.to_vec();
+    let mut seen:Vec<String>=vec![];
+    while let Some(command)=args.first().cloned()
     {
-        let filename=path.clone();
-        let mut file=load_reference(&reference,objects,filename);
-        let SHA1id=getSHA1(&serialize(&file).unwrap());
-        if objects.blobmap.contains_key(&SHA1id)
+        if COMMANDS.contains(&command.as_str())
         {
-            objects.blobmap.remove(&SHA1id);
+            break;
         }
-        if reference.borrow_mut().refermap.contains_key(&path)
+        let Some(expanded)=config.alias(&command) else {break};
+        if seen.contains(&command) || expanded.is_empty()
         {
-            reference.borrow_mut().refermap.remove(&path);
+            return Err(format!("bad alias '{}'",command).into());
         }
-        let mut fathername=fatherName(&(path));
-        let mut sonname=path.clone();
-        let mut sonpath=p.clone();
-        let mut sonobj=Rc::new(RefCell::new(tree::new(String::from(""))));
-        while !fathername.eq("")
-        {
-            let tree_SHA1=reference.borrow_mut().refermap.get(&fathername).cloned().unwrap();
-            let mut tree_rc=objects.treemap.get(&tree_SHA1).cloned().unwrap(); //获取文件父亲树
-            let tree_temp=tree_rc.clone();
-            if sonpath.clone().is_file()
-            {
-                tree_temp.borrow_mut().blobs.remove(&sonname.clone());
-            }
-            sonname=fathername.clone();
-            sonpath=Path::new(&sonname);
-            fathername=fatherName(&(sonname));
-            sonobj=tree_temp.clone();
-            let SHA1id_temp=getSHA1(&serialize(&sonobj).unwrap());
-            objects.treemap.insert(SHA1id_temp.clone(),sonobj.clone());
-            reference.borrow_mut().refermap.insert(sonname.clone(),SHA1id_temp.clone());
-        }
-
-/*        let mut f_obj=File::create("./.gitc/maps/objs.txt");
-        f_obj.unwrap().write(&serialize(&objects).unwrap());
-
-        let mut f_ref=File::create("./.gitc/maps/refs.txt");
-        f_ref.unwrap().write(&serialize(&reference).unwrap());*/
+        seen.push(command);
+        args.splice(0..1,expanded);
     }
+    Ok(args)
 }
 
-pub fn gitInit()
+//gitc config的参数
+fn configCommand(rest:&[String],root:Option<&Path>)->Result<(),Box<dyn Error>>
 {
-    let target_path = Path::new("./.gitc");
-    if target_path.exists()==false
+    let scope=if rest.iter().any(|arg| arg=="--global")
     {
-        fs::create_dir("./.gitc");
-
-    }
-    let head_path=Path::new("./.gitc/HEAD");
-    if  head_path.exists()==false
-    {
-        let mut f=File::create("./.gitc/HEAD");
-        f.unwrap().write(b"ref: refs/heads/master");
+        Some(ConfigScope::User)
     }
-    let objects_path = Path::new("./.gitc/objects");
-    if objects_path.exists()==false
+    else if rest.iter().any(|arg| arg=="--local")
     {
-        fs::create_dir("./.gitc/objects");
-    }
-    let refs_path = Path::new("./.gitc/refs");
-    if refs_path.exists()==false
-    {
-        fs::create_dir("./.gitc/refs");
-
+        Some(ConfigScope::Repository)
     }
-    let refsh_path = Path::new("./.gitc/refs/heads");
-    if refsh_path.exists()==false
-    {
-        fs::create_dir("./.gitc/refs/heads");
-
-    }
-    let refst_path = Path::new("./.gitc/refs/tags");
-    if refst_path.exists()==false
+    else {
+        None
+    };
+    let words=positionals(rest,&[]);
+    let action=if rest.iter().any(|arg| arg=="--list" || arg=="-l")
     {
-        fs::create_dir("./.gitc/refs/tags");
-
+        ConfigAction::List
     }
-
+    else {
+        match words.iter().map(String::as_str).collect::<Vec<_>>().as_slice()
+        {
+            ["get",key]=>ConfigAction::Get(key.to_string()),
+            ["set",key,value]=>ConfigAction::Set(key.to_string(),value.to_string()),
+            ["unset",key]=>ConfigAction::Unset(key.to_string()),
+            _=>return Err("usage: gitc config [--global | --local] (get <key> | set <key> <value> | unset <key> | --list)".into()),
+        }
+    };
+    gitConfig(&action,scope,root)?;
+    Ok(())
 }
-pub fn load_reference(references:&Rc<RefCell<References>>,objects:&Objects,name_or_id:String)->Rc<RefCell<blob>>
+
+fn run(args:&[String])->Result<(),Box<dyn Error>>
 {
-    if references.borrow_mut().refermap.contains_key(&name_or_id)
+    let options=globalOptions(args)?;
+    let args=&args[options.command..];
+    //init在当前目录创建仓库；其他命令先找到仓库的根目录，之后才能读到仓库的配置和别名。
+    //help和config --global在仓库之外也能用，所以找不到仓库时到真正打开仓库时才报错
+    let located=match args.first()
     {
-        let mut res=objects.blobmap.get(references.borrow_mut().refermap.get(&name_or_id).unwrap()).cloned();
-        return res.unwrap();
-    }
-    else
+        Some(command) if command=="init"=>Err(GitcError::NotARepository),
+        _=>Repository::locate(&options.start,options.gitDir.as_deref()),
+    };
+    let root=located.as_ref().ok().map(|(root,_)| root.as_path());
+    let args=expandAlias(&Config::load(root)?,args)?;
+    let command=match args.first()
     {
-        let mut res=objects.blobmap.get(&name_or_id).cloned();
-        return res.unwrap();
-    }
-}
-
-pub fn load_reference_tree(references:&Rc<RefCell<References>>,objects:&Objects,name_or_id:String)->Rc<RefCell<tree>>
-{
-    if references.borrow_mut().refermap.contains_key(&name_or_id)
+        Some(command)=>command.as_str(),
+        None=>return Err(USAGE.into()),
+    };
+    let rest=&args[1..];
+    if command=="help" || command=="--help" || command=="-h"
     {
-        let mut res=objects.treemap.get(references.borrow_mut().refermap.get(&name_or_id).unwrap()).cloned();
-        return res.unwrap();
+        println!("{}",USAGE);
+        return Ok(());
     }
-    else
+    if command=="init"
     {
-        let mut res=objects.treemap.get(&name_or_id).cloned();
-        return res.unwrap();
+        gitInit(&options.start)?;
+        println!("Initialized empty gitc repository in ./.gitc");
+        return Ok(());
     }
-}
-
-
-pub fn getSHA1(data:&[u8])->String
-{
-    let mut hasher = Sha1::new();
-    hasher.update(data);
-    let mut res=String::new();
-    let result = hasher.finalize();
-    for i in result.iter()
-    {
-        let a1:u8=15;
-        let a2:u8=240;
-        let r1=i&a1;
-        let r2=(i&a2)/16;
-
-        res.push(Hex[r1 as usize]);
-        res.push(Hex[r2 as usize]);
-    }
-    return res;
-}*/
-
-/*#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-pub struct Branch
-{
-    name:String,
-    commitpointer:Option<Rc<RefCell<Commit>>>,
-    references:Rc<RefCell<References>>,
-}
-
-impl Branch {
-    pub fn new(name:String)->Self
+    if command=="config"
     {
-        Branch{
-            name:name,
-            commitpointer:Some(Rc::new(RefCell::new(Commit::new()))),
-            references:Rc::new(RefCell::new(References::new()))
-        }
+        return configCommand(rest,root);
     }
-}
-
-
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-pub struct HEAD
-{
-    currentBranchName:String,
-    branch:HashMap<String,Rc<RefCell<Branch>>>,
-}
-
-impl HEAD {
-    pub fn new()->Self
+    let (root,prefix)=located?;
+    let mut repo=Repository::load(root,prefix)?;
+    match command
     {
-        HEAD
+        "add"=>
+        {
+            let paths:Vec<String>=positionals(rest,&[]).iter().map(|path| repo.normalize(path)).collect::<Result<_,_>>()?;
+            let mode=if rest.iter().any(|arg| arg=="-A" || arg=="--all")
+            {
+                AddMode::All
+            }
+            else if rest.iter().any(|arg| arg=="-u" || arg=="--update")
+            {
+                AddMode::Update
+            }
+            else {
+                AddMode::Default
+            };
+            if paths.is_empty() && mode==AddMode::Default
+            {
+                return Err("nothing specified, nothing added".into());
+            }
+            gitAddPathspecs(&mut repo,&paths,mode,true)?;
+        }
+        "rm"=>
+        {
+            let paths=positionals(rest,&[]);
+            if paths.is_empty()
+            {
+                return Err("no pathspec given".into());
+            }
+            let cached=rest.iter().any(|arg| arg=="--cached");
+            let recursive=rest.iter().any(|arg| arg=="-r");
+            let force=rest.iter().any(|arg| arg=="-f" || arg=="--force");
+            for path in paths
+            {
+                let normalized=repo.normalize(&path)?;
+                gitRm(&mut repo,normalized,cached,recursive,force,true)?;
+                println!("rm '{}'",path);
+            }
+        }
+        "commit"=>
         {
-            currentBranchName:String::from(""),
-            branch:HashMap::new(),
+            let message=optionValue(rest,"-m","--message")?.ok_or("commit requires a message (-m <msg>)")?;
+            let author=optionValue(rest,"--author","--author")?.or_else(|| repo.head.config.identity()).unwrap_or_else(defaultAuthor);
+            gitCommit(&mut repo,&message,&author,true)?;
         }
-    }
-    pub fn addbranch(&mut self,branchname:String)
-    {
-        if self.branch.contains_key(&branchname)==false
+        "branch"=>
         {
-            let mut newbranch=Branch::new(branchname.clone());
-
-            for (key,value) in self.branch.get(&self.currentBranchName).cloned().unwrap().borrow().references.borrow().refermap.clone()
+            match positionals(rest,&[]).first()
             {
-                newbranch.references.borrow_mut().refermap.insert(key,value);
+                Some(name)=>gitBranch(&mut repo,name.clone(),true)?,
+                None=>
+                {
+                    for name in repo.head.branch.keys()
+                    {
+                        let mark=if *name==repo.head.currentBranchName {"*"} else {" "};
+                        println!("{} {}",mark,name);
+                    }
+                }
             }
-            newbranch.commitpointer=self.branch.get(&self.currentBranchName).cloned().unwrap().borrow().commitpointer.clone();
-            self.branch.insert(branchname.clone(),Rc::new(RefCell::new(newbranch)));
         }
-        else {
-            println!("This name is contained.Try another name");
+        "checkout"=>
+        {
+            let name=positionals(rest,&[]).first().cloned().ok_or("checkout requires a branch name")?;
+            let force=rest.iter().any(|arg| arg=="-f" || arg=="--force");
+            gitCheckout(&mut repo,name.clone(),force,true)?;
+            println!("Switched to branch '{}'",name);
         }
-
-    }
-    pub fn checkout(&mut self,branchname:String)
-    {
-        if self.branch.contains_key(&branchname)
+        "merge"=>
+        {
+            let name=positionals(rest,&["-m","--message","--author"]).first().cloned().ok_or("merge requires a branch name")?;
+            let message=optionValue(rest,"-m","--message")?.unwrap_or_else(|| format!("Merge branch '{}'",name));
+            let author=optionValue(rest,"--author","--author")?.or_else(|| repo.head.config.identity()).unwrap_or_else(defaultAuthor);
+            let mode=if rest.iter().any(|arg| arg=="--no-ff")
+            {
+                MergeMode::NoFastForward
+            }
+            else if rest.iter().any(|arg| arg=="--ff-only")
+            {
+                MergeMode::FastForwardOnly
+            }
+            else {
+                MergeMode::fromConfig(&repo.head.config)?
+            };
+            let result=gitMerge(&mut repo,name,&message,&author,mode,true)?;
+            match result.outcome
+            {
+                MergeOutcome::UpToDate=>println!("Already up to date."),
+                MergeOutcome::FastForward=>println!("Fast-forward"),
+                MergeOutcome::Merged=>println!("Merge made by the three-way strategy."),
+                MergeOutcome::Conflicted=>
+                {
+                    for path in result.conflicts.iter()
+                    {
+                        println!("CONFLICT: Merge conflict in {}",path);
+                    }
+                    return Err("Automatic merge failed; fix conflicts and then commit the result.".into());
+                }
+            }
+        }
+        "merge-base"=>
+        {
+            let commits=positionals(rest,&[]);
+            if commits.len()!=2
+            {
+                return Err("merge-base requires exactly two commits".into());
+            }
+            let bases=gitMergeBase(&repo,&commits[0],&commits[1])?;
+            if bases.is_empty()
+            {
+                return Err("no common ancestor".into());
+            }
+            let count=if rest.iter().any(|arg| arg=="--all") {bases.len()} else {1};
+            for id in bases.iter().take(count)
+            {
+                println!("{}",id);
+            }
+        }
+        "rev-parse"=>
+        {
+            let revisions=positionals(rest,&[]);
+            if revisions.len()!=1
+            {
+                return Err("rev-parse requires exactly one revision".into());
+            }
+            gitRevParse(&repo,&revisions[0])?;
+        }
+        "diff"=>
+        {
+            let mut options=DiffOptions::fromConfig(&repo.head.config)?;
+            let context=match rest.iter().find_map(|arg| arg.strip_prefix("-U").filter(|n| !n.is_empty()))
+            {
+                Some(n)=>Some(n.to_string()),
+                None=>optionValue(rest,"-U","--unified")?,
+            };
+            if let Some(n)=context
+            {
+                options.context=n.parse().map_err(|_| format!("invalid context length '{}'",n))?;
+            }
+            if rest.iter().any(|arg| arg=="--patience")
+            {
+                options.algorithm=DiffAlgorithm::Patience;
+            }
+            if rest.iter().any(|arg| arg=="--histogram")
+            {
+                options.algorithm=DiffAlgorithm::Histogram;
+            }
+            if let Some(name)=optionValue(rest,"--diff-algorithm","--diff-algorithm")?
+            {
+                options.algorithm=DiffAlgorithm::fromName(&name).ok_or_else(|| format!("unknown diff algorithm '{}'",name))?;
+            }
+            let commits=positionals(rest,&["-U","--unified","--diff-algorithm"]);
+            let mode=match commits.as_slice()
+            {
+                []=>if rest.iter().any(|arg| arg=="--cached" || arg=="--staged") {DiffMode::Cached} else {DiffMode::WorkingTree},
+                [from,to]=>DiffMode::Commits(from.clone(),to.clone()),
+                _=>return Err("diff takes either no commit or two commits".into()),
+            };
+            gitDiff(&repo,&mode,&options)?;
+        }
+        "log"=>
+        {
+            let (rest,path)=match rest.iter().position(|arg| arg=="--")
+            {
+                Some(split)=>(&rest[..split],rest.get(split+1).map(|path| repo.normalize(path)).transpose()?),
+                None=>(rest,None),
+            };
+            let count=match rest.iter().find_map(|arg| arg.strip_prefix('-').filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())))
+            {
+                Some(n)=>Some(n.to_string()),
+                None=>optionValue(rest,"-n","--max-count")?,
+            };
+            let options=LogOptions
+            {
+                start:positionals(rest,&["-n","--max-count","--author","--grep","--since","--until"]).first().cloned(),
+                oneline:rest.iter().any(|arg| arg=="--oneline"),
+                graph:rest.iter().any(|arg| arg=="--graph"),
+                maxCount:count.map(|n| n.parse().map_err(|_| format!("invalid count '{}'",n))).transpose()?,
+                author:optionValue(rest,"--author","--author")?,
+                grep:optionValue(rest,"--grep","--grep")?,
+                since:optionValue(rest,"--since","--since")?.map(|date| logDate(&date)).transpose()?,
+                until:optionValue(rest,"--until","--until")?.map(|date| logDate(&date)).transpose()?,
+                path,
+            };
+            gitLog(&repo,&options)?;
+        }
+        "count-objects"=>
+        {
+            let stats=storeStats(&repo.root)?;
+            println!("count: {}",stats.count);
+            println!("size: {}",stats.rawSize);
+            println!("size-compressed: {}",stats.compressedSize);
+            if stats.rawSize>0
+            {
+                println!("saved: {:.1}%",100.0-100.0*stats.compressedSize as f64/stats.rawSize as f64);
+            }
+        }
+        "repack"=>
+        {
+            let summary=gitRepack(&repo)?;
+            println!("Packed {} objects ({} deltas)",summary.objects,summary.deltas);
+        }
+        "gc"=>
+        {
+            let prune=match rest.iter().find_map(|arg| arg.strip_prefix("--prune="))
+            {
+                Some(value)=>parsePrune(value).ok_or_else(|| format!("invalid prune time '{}'",value))?,
+                None=>pruneExpire(&repo.head.config)?,
+            };
+            let summary=gitGc(&mut repo,prune)?;
+            println!("{} reachable objects, {} pruned, {} unreachable kept",summary.reachable,summary.pruned,summary.kept);
+        }
+        "clean"=>
+        {
+            let has=|flag:char| rest.iter().any(|arg| arg.len()>1 && arg.starts_with('-') && !arg.starts_with("--") && arg.contains(flag));
+            let options=CleanOptions{dryRun:has('n') || rest.iter().any(|arg| arg=="--dry-run"),directories:has('d'),ignored:has('x'),onlyIgnored:has('X')};
+            if !options.dryRun && !has('f') && !rest.iter().any(|arg| arg=="--force")
+            {
+                return Err("refusing to clean without -n or -f".into());
+            }
+            gitClean(&repo,&options)?;
+        }
+        "check-ignore"=>
+        {
+            let paths:Vec<String>=positionals(rest,&[]).iter().map(|path| repo.normalize(path)).collect::<Result<_,_>>()?;
+            if paths.is_empty()
+            {
+                return Err("no path specified".into());
+            }
+            gitCheckIgnore(&repo,&paths,rest.iter().any(|arg| arg=="-v" || arg=="--verbose"))?;
+        }
+        "status"=>
         {
-            self.currentBranchName=branchname;
+            let porcelain=rest.iter().any(|arg| arg=="--porcelain" || arg=="-s" || arg=="--short");
+            gitStatus(&repo,porcelain)?;
         }
-           else {
-               println!("uncontained name!");
-           }
+        _=>return Err(format!("'{}' is not a gitc command. See 'gitc --help'.",command).into()),
     }
+    Ok(())
+}
 
-    pub fn showAllBranch(&self)
+fn main() {
+    let args:Vec<String>=std::env::args().skip(1).collect();
+    if let Err(message)=run(&args)
     {
-        for (key,value) in self.branch.iter()
+        if args.is_empty()
         {
-            println!("{:?}",key);
+            eprintln!("{}",message);
+        }
+        else {
+            eprintln!("fatal: {}",message);
         }
+        process::exit(1);
     }
-
 }
 
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-enum objecttype
-{
-    commit,
-    tree,
-    blob
-}
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize,Clone)]
-pub struct References
-{
-    refermap:HashMap<String,String>,
-}
+#[cfg(test)]
+static SANDBOX_LOCK:std::sync::Mutex<()>=std::sync::Mutex::new(());
 
-impl References {
-    pub fn new()->Self
+//测试都基于当前目录运行，这里为每个测试复制一份示例文件到临时目录并切换过去，
+//同时持有一把全局锁，避免并行的测试互相修改同一个.gitc
+#[cfg(test)]
+fn sandbox(name:&str)->std::sync::MutexGuard<'static,()>
+{
+    let guard=SANDBOX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let root=Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir=std::env::temp_dir().join(format!("gitc-{}-{}",name,process::id()));
+    if dir.exists()
     {
-        References
-        {
-            refermap:HashMap::new(),
-        }
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-    pub fn update_reference(&mut self,name:String,id:String)
+    std::fs::create_dir_all(dir.join("demo")).unwrap();
+    for file in ["hello.txt","hello1.txt","hello2.txt","demo/demo1.txt","demo/demo2.txt"]
     {
-        self.refermap.insert(name,id);
+        std::fs::copy(root.join(file),dir.join(file)).unwrap();
     }
-    pub fn read_reference(&self,name:String)->String
+    std::env::set_current_dir(&dir).unwrap();
+    //新commit的时间固定为0，SHA1不随运行时间变化
+    for role in ["AUTHOR","COMMITTER"]
     {
-        let id=String::from(self.refermap.get(&name).unwrap());
-        return id;
+        std::env::remove_var(format!("GITC_{}_NAME",role));
+        std::env::remove_var(format!("GITC_{}_EMAIL",role));
+        std::env::set_var(format!("GITC_{}_DATE",role),"0 +0000");
     }
-}
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-pub struct Objects
-{
-    treemap:HashMap<String,Rc<RefCell<tree>>>,
-    commitmap:HashMap<String,Rc<RefCell<Commit>>>,
-    blobmap:HashMap<String,Rc<RefCell<blob>>>,
+    //不读取真正的~/.gitcconfig
+    std::env::set_var("GITC_CONFIG_GLOBAL",dir.join("gitcconfig"));
+    std::env::remove_var("GITC_CONFIG_COUNT");
+    gitInit(Path::new(".")).unwrap();
+    guard
 }
 
-impl Objects {
-    pub fn new()->Self
-    {
-        Objects
-        {
-            treemap:HashMap::new(),
-            commitmap:HashMap::new(),
-            blobmap:HashMap::new(),
-        }
-    }
-
-}
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-pub struct Commit
+//分支最新的commit
+#[cfg(test)]
+fn tipCommit(head:&HEAD,objects:&Objects,name:&str)->Rc<RefCell<Commit>>
 {
-    parents:Vec<Option<Rc<RefCell<Commit>>>>,
-    mergeparents:Vec<Option<Rc<RefCell<Commit>>>>,
-    author:String ,
-    message:String,
-    snapshot:Rc<RefCell<tree>>,
-    t:objecttype,
+    objects.loadCommit(head.branch.get(name).unwrap().borrow().commitpointer.as_ref().unwrap()).unwrap()
 }
 
-impl Commit {
-    pub fn new()->Self
-    {
-        Commit
-        {
-            parents:Vec::new(),
-            mergeparents:Vec::new(),
-            author:String::new(),
-            message:String::new(),
-            snapshot:Rc::new(RefCell::new(tree::new(String::from(".")))),
-            t:objecttype::commit,
-
-        }
-    }
-}
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-pub struct tree
+//commit快照中每个文件的内容
+#[cfg(test)]
+fn commitContents(objects:&Objects,commit:&Rc<RefCell<Commit>>)->BTreeMap<String,Vec<u8>>
 {
-    name:String,
-    trees:HashMap<String,Rc<RefCell<tree>>>,
-    blobs:HashMap<String,Rc<RefCell<blob>>>,
-    t:objecttype,
+    snapshotBlobs(objects,&commit.borrow().snapshot).unwrap().into_iter()
+        .map(|(path,id)| (path,objects.blobContents(&id).unwrap()))
+        .collect()
 }
 
-impl tree {
-    pub fn new(name:String)->Self
-    {
-        tree
-        {   name:String::from(name),
-            trees:HashMap::new(),
-            blobs:HashMap::new(),
-            t:objecttype::tree,
-        }
-    }
-}
-#[repr(C)]
-#[derive(Debug,Serialize,Deserialize)]
-pub struct blob
+//按版本7之前的格式复制一个tree：子tree和blob直接嵌套保存
+#[cfg(test)]
+fn legacyTree(objects:&Objects,id:&str,name:&str)->Rc<RefCell<LegacyTree>>
 {
-    name:String,
-    contents:Vec<u8>,
-    t:objecttype,
-
-}
-
-impl  blob {
-    pub fn new(name:String)->Self
+    let node=objects.loadTree(id,name).unwrap();
+    let mut res=LegacyTree::new(name.to_string());
+    for (path,blobId) in node.borrow().blobs.iter()
     {
-        blob
-        {
-            name:String::from(name),
-            contents:vec![],
-            t:objecttype::blob,
-        }
+        res.blobs.insert(path.clone(),objects.loadBlob(blobId,path).unwrap());
     }
-    pub fn getContents(&mut self,path:String)
+    for (path,treeId) in node.borrow().trees.iter()
     {
-        let mut file_open = fs::File::open(path).unwrap();
-        file_open.read_to_end(&mut self.contents);
+        res.trees.insert(path.clone(),legacyTree(objects,treeId,path));
     }
+    Rc::new(RefCell::new(res))
+}
 
-
-}*/
-
-fn main() {
-
-    let mut objects;
-    if Path::new("./.gitc/maps/objs").exists()
+//按版本6之前的格式复制一个commit：parents和mergeparents中是整条祖先链，最后一个才是直接的父commit
+#[cfg(test)]
+fn legacyCommit(objects:&Objects,id:&str)->Rc<RefCell<LegacyCommit>>
+{
+    let commit=objects.loadCommit(id).unwrap();
+    let mut res=LegacyCommit::new();
+    res.author=commit.borrow().author.name.clone();
+    res.message=commit.borrow().message.clone();
+    res.snapshot=legacyTree(objects,&commit.borrow().snapshot,".");
+    for parent in commit.borrow().parents.iter()
     {
-         let mut buffer: Vec<u8> = vec![];
-         File::open("./.gitc/maps/objs").unwrap().read_to_end(&mut buffer);
-         objects=deserialize(&buffer).unwrap();
-
+        let old=legacyCommit(objects,parent);
+        res.parents.extend(old.borrow().parents.iter().cloned());
+        res.parents.push(Some(old));
     }
-    else {
-        objects=Objects::new();
+    for parent in commit.borrow().mergeparents.iter()
+    {
+        let old=legacyCommit(objects,parent);
+        res.mergeparents.extend(old.borrow().mergeparents.iter().cloned());
+        res.mergeparents.push(Some(old));
     }
+    Rc::new(RefCell::new(res))
+}
 
-    let mut head:HEAD;
-    if Path::new("./.gitc/maps/head").exists()
+//按版本7之前的格式复制head，还没有提交过的分支指向占位commit
+#[cfg(test)]
+fn legacyHead(head:&HEAD,objects:&Objects)->LegacyHEAD
+{
+    let mut res=LegacyHEAD{currentBranchName:head.currentBranchName.clone(),branch:BTreeMap::new(),mergeHead:head.mergeHead.clone()};
+    for (name,branch) in head.branch.iter()
     {
-        let mut buffer: Vec<u8> = vec![];
-        File::open("./.gitc/maps/head").unwrap().read_to_end(&mut buffer);
-        head=deserialize(&buffer).unwrap();
-    }
-    else {
-        head=HEAD::new();
-        head.currentBranchName=String::from("master");
-        head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+        let commitpointer=match branch.borrow().commitpointer.as_ref()
+        {
+            Some(id)=>legacyCommit(objects,id),
+            None=>Rc::new(RefCell::new(LegacyCommit::new())),
+        };
+        let old=LegacyBranch{name:name.clone(),commitpointer:Some(commitpointer),references:branch.borrow().references.clone()};
+        res.branch.insert(name.clone(),Rc::new(RefCell::new(old)));
     }
-
-
-    //println!("{:?}",objects.blobmap);
-    //println!("{:?}",objects.treemap);
-    //println!("{:?}",objects.commitmap);
+    res
 }
 
 #[test]
 fn add_test() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("add_test");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
 
-    for (k,v) in objects.blobmap
+    for (k,v) in repo.objects.blobmap
     {
         println!("{:?},{:?}",k,v);
     }
@@ -722,18 +668,18 @@ fn add_test() {
 
 #[test]
 fn add_test_save() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("add_test_save");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,true);
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,true);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),true).unwrap();
 
-    for (k,v) in objects.blobmap
+    for (k,v) in repo.objects.blobmap
     {
         println!("{:?},{:?}",k,v);
     }
@@ -741,20 +687,24 @@ fn add_test_save() {
 
 #[test]
 fn rm_test() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("rm_test");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
 
-    gitRm("./hello.txt".to_string(),&mut objects,&mut head,false);
+    //还没有提交过的文件只存在于暂存区，需要-f才能连同工作区一起删除
+    assert!(matches!(gitRm(&mut repo,"./hello.txt".to_string(),false,false,false,false),Err(GitcError::LocalModifications(paths)) if paths==vec![String::from("./hello.txt")]));
+    assert!(Path::new("./hello.txt").is_file());
+    gitRm(&mut repo,"./hello.txt".to_string(),false,false,true,false).unwrap();
+    assert!(!Path::new("./hello.txt").exists());
 
-    for (k,v) in objects.blobmap
+    for (k,v) in repo.objects.blobmap
     {
         println!("{:?},{:?}",k,v);
     }
@@ -762,30 +712,30 @@ fn rm_test() {
 
 #[test]
 fn commit_test() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("commit_test");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
 
-    gitCommit(&mut head,&mut objects,"master_first","alex",false);
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
 
-    for (k,v) in objects.blobmap
+    for (k,v) in repo.objects.blobmap
     {
         println!("{:?},{:?}",k,v);
     }
     println!("-------------");
-    for (k,v) in objects.treemap
+    for (k,v) in repo.objects.treemap
     {
         println!("{:?},{:?}",k,v);
     }
     println!("-------------");
-    for (k,v) in objects.commitmap
+    for (k,v) in repo.objects.commitmap
     {
         println!("{:?},{:?}",k,v.borrow().snapshot);
     }
@@ -794,55 +744,55 @@ fn commit_test() {
 
 #[test]
 fn branch_test() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("branch_test");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
 
-    gitCommit(&mut head,&mut objects,"master_first","alex",false);
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
 
-    gitBranch(&mut head,String::from("b1"),false);
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
 
-    gitCheckout(&mut head,String::from("b1"),false);
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
 
-    for (k,v) in head.branch.clone()
+    for k in repo.head.branch.keys()
     {
         println!("branchname:{:?}",k);
     }
     println!("-------------");
-    let branch1=head.branch.get(&String::from("master")).cloned().unwrap();
-    let branch2=head.branch.get(&String::from("b1")).cloned().unwrap();
+    let branch1=repo.head.branch.get(&String::from("master")).cloned().unwrap();
+    let branch2=repo.head.branch.get(&String::from("b1")).cloned().unwrap();
 
     assert_eq!(branch1.borrow().commitpointer,branch2.borrow().commitpointer);
     assert_eq!(branch1.borrow().references,branch2.borrow().references);
 
-    gitAdd("./hello1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
 
-    gitCommit(&mut head,&mut objects,"b1_first","alex",false);
+    gitCommit(&mut repo,"b1_first","alex",false).unwrap();
 
-    println!("{:?}",head.branch.get(&String::from("master")).cloned().unwrap().borrow().references);
+    println!("{:?}",repo.head.branch.get(&String::from("master")).cloned().unwrap().borrow().references);
     println!("-------------");
 
-    println!("{:?}",head.branch.get(&String::from("b1")).cloned().unwrap().borrow().references);
+    println!("{:?}",repo.head.branch.get(&String::from("b1")).cloned().unwrap().borrow().references);
     println!("-------------");
 
-    for (k,v) in objects.blobmap
+    for (k,v) in repo.objects.blobmap
     {
         println!("{:?},{:?}",k,v);
     }
     println!("-------------");
-    for (k,v) in objects.treemap
+    for (k,v) in repo.objects.treemap
     {
         println!("{:?},{:?}",k,v);
     }
     println!("-------------");
-    for (k,v) in objects.commitmap
+    for (k,v) in repo.objects.commitmap
     {
         println!("{:?},{:?}",k,v.borrow().message);
     }
@@ -852,123 +802,1549 @@ fn branch_test() {
 
 #[test]
 fn merge_test() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("merge_test");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
 
-    gitCommit(&mut head,&mut objects,"master_first","alex",false);
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
 
 
-    gitBranch(&mut head,String::from("b1"),false);
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
 
-    gitCheckout(&mut head,String::from("b1"),false);
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
 
-    gitAdd("./hello1.txt".to_string(),&mut objects,&mut head,false);
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
 
-    gitCommit(&mut head,&mut objects,"b1_first","alex",false);
+    gitCommit(&mut repo,"b1_first","alex",false).unwrap();
 
-    gitCheckout(&mut head,String::from("master"),false);
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
 
-    gitMerge(&mut head,String::from("b1"),"merge_master_b1","alex",&mut objects,false);
+    gitMerge(&mut repo,String::from("b1"),"merge_master_b1","alex",MergeMode::NoFastForward,false).unwrap();
 
 
-    println!("{:?}",head.branch.get(&String::from("master")).cloned().unwrap().borrow().references);
+    println!("{:?}",repo.head.branch.get(&String::from("master")).cloned().unwrap().borrow().references);
     println!("---------------");
-    let commit=head.branch.get(&String::from("master")).cloned().unwrap().clone().borrow().commitpointer.clone();
-    println!("{:?}",commit.clone().unwrap().borrow().snapshot.clone());
+    let commit=repo.objects.loadCommit(&repo.head.branch.get(&String::from("master")).cloned().unwrap().borrow().commitpointer.clone().unwrap()).unwrap();
+    println!("{:?}",commit.borrow().snapshot.clone());
     println!("---------------");
-    for i in commit.clone().unwrap().borrow().parents.clone()
+    for i in commit.borrow().parents.iter()
     {
-        if i.is_some()
-        {
-            println!("parentname:{:?}",i.unwrap().borrow().message);
-        }
+        println!("parentname:{:?}",repo.objects.loadCommit(i).unwrap().borrow().message);
     }
     println!("---------------");
-    for i in commit.clone().unwrap().borrow().mergeparents.clone()
+    for i in commit.borrow().mergeparents.iter()
     {
-        if i.is_some()
-        {
-            println!("parentname:{:?}",i.unwrap().borrow().message);
-        }
+        println!("parentname:{:?}",repo.objects.loadCommit(i).unwrap().borrow().message);
     }
 
 }
 #[test]
 fn merge_test_save() {
-    let mut head:HEAD=HEAD::new();
+    let _guard=sandbox("merge_test_save");
 
-    head.currentBranchName=String::from("master");
-    head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
 
-    let mut objects=Objects::new();
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
 
-    gitAdd("./hello.txt".to_string(),&mut objects,&mut head,true);
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
 
-    gitAdd("./demo/demo1.txt".to_string(),&mut objects,&mut head,true);
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),true).unwrap();
 
-    gitCommit(&mut head,&mut objects,"master_first","alex",true);
+    gitCommit(&mut repo,"master_first","alex",true).unwrap();
 
-    gitBranch(&mut head,String::from("b1"),true);
+    gitBranch(&mut repo,String::from("b1"),true).unwrap();
 
-    gitCheckout(&mut head,String::from("b1"),true);
+    gitCheckout(&mut repo,String::from("b1"),false,true).unwrap();
 
 
 
-    gitAdd("./hello1.txt".to_string(),&mut objects,&mut head,true);
+    gitAdd(&mut repo,"./hello1.txt".to_string(),true).unwrap();
 
-    gitCommit(&mut head,&mut objects,"b1_first","alex",true);
+    gitCommit(&mut repo,"b1_first","alex",true).unwrap();
 
-    gitCheckout(&mut head,String::from("master"),true);
+    gitCheckout(&mut repo,String::from("master"),false,true).unwrap();
 
-    gitMerge(&mut head,String::from("b1"),"merge_master_b1","alex",&mut objects,true);
+    gitMerge(&mut repo,String::from("b1"),"merge_master_b1","alex",MergeMode::NoFastForward,true).unwrap();
 
-    println!("branchname:{:?}",head.currentBranchName);
+    println!("branchname:{:?}",repo.head.currentBranchName);
 
-    println!("{:?}",head.branch.get(&String::from("master")).cloned().unwrap().borrow().references);
+    println!("{:?}",repo.head.branch.get(&String::from("master")).cloned().unwrap().borrow().references);
 
-    let commit=head.branch.get(&String::from("master")).cloned().unwrap().clone().borrow().commitpointer.clone();
-    for i in commit.clone().unwrap().borrow().parents.clone()
+    let commitid=repo.head.branch.get(&String::from("master")).cloned().unwrap().borrow().commitpointer.clone().unwrap();
+    let commit=repo.objects.loadCommit(&commitid).unwrap();
+    for i in commit.borrow().parents.iter()
     {
-        if i.is_some()
-        {
-            println!("parentname:{:?}",i.unwrap().borrow().message);
-        }
+        println!("parentname:{:?}",repo.objects.loadCommit(i).unwrap().borrow().message);
     }
     println!("--------------");
-    for i in commit.clone().unwrap().borrow().mergeparents.clone()
+    for i in commit.borrow().mergeparents.iter()
     {
-        if i.is_some()
-        {
-            println!("parentname:{:?}",i.unwrap().borrow().message);
-        }
+        println!("parentname:{:?}",repo.objects.loadCommit(i).unwrap().borrow().message);
     }
     //println!("{:?}",commit.clone().unwrap().borrow().mergeparents.len());
 
     let mut buffer: Vec<u8> = vec![];
-    File::open("./.gitc/maps/head").unwrap().read_to_end(&mut buffer);
-    let load_head:HEAD=deserialize(&buffer).unwrap();
-    buffer.clear();
-    File::open("./.gitc/maps/objs").unwrap().read_to_end(&mut buffer);
-    let load_obj:Objects=deserialize(&buffer).unwrap();
+    File::open("./.gitc/maps/head").unwrap().read_to_end(&mut buffer).unwrap();
+    let _load_head:HEAD=deserialize(&buffer).unwrap();
+    //对象不再写入maps/objs，而是从./.gitc/objects中按SHA1读出
+    let (load_obj,_)=loadState(Path::new(".")).unwrap();
+    let _load_commit=load_obj.loadCommit(&commitid).unwrap();
 
-/*    assert_eq!(objects,load_obj);
-    assert_eq!(head,load_head);
+/*    assert_eq!(repo.objects,load_obj);
+    assert_eq!(repo.head,load_head);
 
     for (k,v) in load_obj.commitmap
     {
         println!("{:?}:{:?}",k,v.borrow().message);
     }
     println!("---------");
-    for (k,v) in objects.commitmap
+    for (k,v) in repo.objects.commitmap
     {
         println!("{:?}:{:?}",k,v.borrow().message);
     }*/
-}
\ No newline at end of file
+}
+
+#[test]
+fn cli_test() {
+    let _guard=sandbox("cli_test");
+
+    let args=|line:&str| line.split(' ').map(String::from).collect::<Vec<String>>();
+
+    run(&args("add hello.txt demo/demo1.txt")).unwrap();
+    run(&args("commit -m master_first --author alex")).unwrap();
+    run(&args("branch b1")).unwrap();
+    assert!(run(&args("branch b1")).is_err());
+    run(&args("checkout b1")).unwrap();
+    run(&args("add ./hello1.txt")).unwrap();
+    run(&args("commit -m b1_first --author=alex")).unwrap();
+    run(&args("checkout master")).unwrap();
+    run(&args("merge b1 --ff-only")).unwrap();
+    run(&args("checkout b1")).unwrap();
+    run(&args("merge master")).unwrap();
+    run(&args("add hello2.txt")).unwrap();
+    run(&args("commit -m b1_second --author alex")).unwrap();
+    run(&args("checkout master")).unwrap();
+    run(&args("merge b1 --no-ff -m merge_master_b1 --author alex")).unwrap();
+
+    assert!(run(&args("checkout nobranch")).is_err());
+    assert!(run(&args("commit --author alex")).is_err());
+    assert!(run(&args("frobnicate")).is_err());
+
+    let repo=Repository::open(None).unwrap();
+    assert_eq!(repo.head.currentBranchName,"master");
+    let commit=tipCommit(&repo.head,&repo.objects,"master");
+    assert_eq!(commit.borrow().message,"merge_master_b1");
+    assert!(snapshotBlobs(&repo.objects,&commit.borrow().snapshot).unwrap().contains_key("./hello2.txt"));
+}
+
+#[test]
+fn error_test() {
+    let _guard=sandbox("error_test");
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    assert!(matches!(gitAdd(&mut repo,"./nofile.txt".to_string(),false),Err(GitcError::PathNotFound(_))));
+    assert!(matches!(gitCommit(&mut repo,"empty","alex",false),Err(GitcError::NothingToCommit)));
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+
+    assert!(matches!(gitRm(&mut repo,"./hello1.txt".to_string(),false,false,false,false),Err(GitcError::ObjectNotFound(_))));
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+    assert!(matches!(gitBranch(&mut repo,String::from("b1"),false),Err(GitcError::BranchExists(_))));
+    assert!(matches!(gitCheckout(&mut repo,String::from("b2"),false,false),Err(GitcError::UnknownBranch(_))));
+    assert!(matches!(gitMerge(&mut repo,String::from("b2"),"merge","alex",MergeMode::FastForward,false),Err(GitcError::UnknownBranch(_))));
+
+    let references=Rc::new(RefCell::new(References::new()));
+    assert!(matches!(load_reference(&references,&repo.objects,String::from("./hello.txt")),Err(GitcError::ObjectNotFound(_))));
+    assert!(matches!(load_reference_tree(&references,&repo.objects,String::from("missing")),Err(GitcError::ObjectNotFound(_))));
+
+    std::fs::write("./.gitc/maps/head",b"not a repo.head").unwrap();
+    assert!(matches!(loadState(Path::new(".")),Err(GitcError::CorruptStore(_))));
+}
+
+#[test]
+fn merge3_test() {
+    let base=b"a\nb\nc\nd\ne\n";
+    let ours=b"A\nb\nc\nd\ne\n";
+    let theirs=b"a\nb\nc\nd\nE\n";
+    let res=merge3(base,ours,theirs,"HEAD","b1",ConflictStyle::Merge);
+    assert_eq!(res.conflicts,0);
+    assert_eq!(res.contents,b"A\nb\nc\nd\nE\n".to_vec());
+
+    let ours=b"a\nb\nours\nd\ne\n";
+    let theirs=b"a\nb\ntheirs\nd\ne\n";
+    let res=merge3(base,ours,theirs,"HEAD","b1",ConflictStyle::Merge);
+    assert_eq!(res.conflicts,1);
+    assert_eq!(String::from_utf8(res.contents).unwrap(),"a\nb\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> b1\nd\ne\n");
+
+    //diff3在两边之间写出base中的内容
+    let res=merge3(base,ours,theirs,"HEAD","b1",ConflictStyle::Diff3);
+    assert_eq!(String::from_utf8(res.contents).unwrap(),"a\nb\n<<<<<<< HEAD\nours\n||||||| base\nc\n=======\ntheirs\n>>>>>>> b1\nd\ne\n");
+
+    let same=merge3(base,b"a\nx\nc\nd\ne\n",b"a\nx\nc\nd\ne\n","HEAD","b1",ConflictStyle::Merge);
+    assert_eq!(same.conflicts,0);
+    assert_eq!(same.contents,b"a\nx\nc\nd\ne\n".to_vec());
+}
+
+#[test]
+fn merge_conflict_test() {
+    let _guard=sandbox("merge_conflict_test");
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    std::fs::write("./hello.txt","line1\nline2\nline3\nline4\nline5\n").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+
+    std::fs::write("./hello.txt","master1\nline2\nline3\nline4\nline5\n").unwrap();
+    std::fs::write("./hello1.txt","master").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_second","alex",false).unwrap();
+
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    std::fs::write("./hello.txt","line1\nline2\nline3\nline4\nb1_5\n").unwrap();
+    std::fs::write("./hello1.txt","b1").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b1_first","alex",false).unwrap();
+
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    //合并会改写的文件有没提交的改动时拒绝合并，不写入任何文件
+    std::fs::write("./hello.txt","MYWORK").unwrap();
+    assert!(matches!(gitMerge(&mut repo,String::from("b1"),"merge_master_b1","alex",MergeMode::FastForward,false),Err(GitcError::WouldOverwrite(operation,paths)) if operation=="merge" && paths==vec![String::from("./hello.txt")]));
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"MYWORK");
+    assert_eq!(std::fs::read_to_string("./hello1.txt").unwrap(),"master");
+    assert!(repo.head.mergeHead.is_none());
+    std::fs::write("./hello.txt","master1\nline2\nline3\nline4\nline5\n").unwrap();
+
+    let result=gitMerge(&mut repo,String::from("b1"),"merge_master_b1","alex",MergeMode::FastForward,false).unwrap();
+
+    //hello.txt两边改的是不同的行，自动合并；hello1.txt两边都改了同一行，产生冲突
+    assert_eq!(result.conflicts,vec![String::from("./hello1.txt")]);
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"master1\nline2\nline3\nline4\nb1_5\n");
+    assert_eq!(std::fs::read_to_string("./hello1.txt").unwrap(),"<<<<<<< HEAD\nmaster\n=======\nb1\n>>>>>>> b1\n");
+    assert_eq!(tipCommit(&repo.head,&repo.objects,"master").borrow().message,"master_second");
+    assert!(matches!(gitMerge(&mut repo,String::from("b1"),"again","alex",MergeMode::FastForward,false),Err(GitcError::MergeInProgress)));
+
+    //冲突的文件记录为未解决，重新暂存之前不能提交
+    repo.save().unwrap();
+    let reopened=Repository::open(None).unwrap();
+    assert_eq!(reopened.head.index.unmerged.iter().collect::<Vec<_>>(),vec!["./hello1.txt"]);
+    let statuses:Vec<String>=statusEntries(&reopened).unwrap().iter().map(|entry| entry.porcelain()).collect();
+    assert!(statuses.contains(&String::from("UU ./hello1.txt")) && statuses.contains(&String::from("M  ./hello.txt")));
+    assert!(matches!(gitCommit(&mut repo,"resolved","alex",false),Err(GitcError::MergeInProgress)));
+    assert_eq!(tipCommit(&repo.head,&repo.objects,"master").borrow().message,"master_second");
+
+    std::fs::write("./hello1.txt","resolved").unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    assert!(repo.head.index.unmerged.is_empty());
+    gitCommit(&mut repo,"merge_master_b1","alex",false).unwrap();
+
+    let commit=tipCommit(&repo.head,&repo.objects,"master");
+    assert_eq!(repo.objects.loadCommit(commit.borrow().mergeparents.last().unwrap()).unwrap().borrow().message,"b1_first");
+    let files=commitContents(&repo.objects,&commit);
+    assert_eq!(files.get("./hello.txt").unwrap(),&b"master1\nline2\nline3\nline4\nb1_5\n".to_vec());
+    assert_eq!(files.get("./hello1.txt").unwrap(),&b"resolved".to_vec());
+    assert!(repo.head.mergeHead.is_none());
+}
+
+#[test]
+fn merge_base_test() {
+    let _guard=sandbox("merge_base_test");
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"A","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"M1","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("m1"),false).unwrap();
+
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    gitAdd(&mut repo,"./hello2.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"B1","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("bb"),false).unwrap();
+
+    let bases=gitMergeBase(&repo,"m1","bb").unwrap();
+    assert_eq!(repo.objects.commitmap.get(&bases[0]).unwrap().borrow().message,"A");
+    assert_eq!(bases.len(),1);
+    assert_eq!(gitMergeBase(&repo,"m1","master").unwrap(),gitMergeBase(&repo,"master","m1").unwrap());
+    assert!(matches!(gitMergeBase(&repo,"m1","nothing"),Err(GitcError::UnknownRevision(_))));
+
+    //交叉合并：master合并B1，b1合并M1，此后两个分支有M1和B1两个最佳共同祖先
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert!(gitMerge(&mut repo,String::from("bb"),"X","alex",MergeMode::FastForward,false).unwrap().conflicts.is_empty());
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    assert!(gitMerge(&mut repo,String::from("m1"),"Y","alex",MergeMode::FastForward,false).unwrap().conflicts.is_empty());
+
+    let bases=gitMergeBase(&repo,"master","b1").unwrap();
+    let mut names:Vec<String>=bases.iter().map(|id| repo.objects.commitmap.get(id).unwrap().borrow().message.clone()).collect();
+    names.sort();
+    assert_eq!(names,vec!["B1","M1"]);
+
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert!(gitMerge(&mut repo,String::from("b1"),"Z","alex",MergeMode::FastForward,false).unwrap().conflicts.is_empty());
+}
+
+#[test]
+fn fast_forward_test() {
+    let _guard=sandbox("fast_forward_test");
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b1_first","alex",false).unwrap();
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+
+    let master=repo.head.branch.get("master").cloned().unwrap();
+    let b1=repo.head.branch.get("b1").cloned().unwrap();
+    let before=master.borrow().commitpointer.clone();
+
+    let result=gitMerge(&mut repo,String::from("master"),"self","alex",MergeMode::FastForward,false).unwrap();
+    assert_eq!(result.outcome,MergeOutcome::UpToDate);
+    assert_eq!(master.borrow().commitpointer,before);
+
+    //被快进改写的文件有没提交的改动时拒绝快进，工作区不变
+    std::fs::write("./hello1.txt","mine").unwrap();
+    assert!(matches!(gitMerge(&mut repo,String::from("b1"),"ff","alex",MergeMode::FastForward,false),Err(GitcError::WouldOverwrite(operation,paths)) if operation=="merge" && paths==vec![String::from("./hello1.txt")]));
+    assert_eq!(std::fs::read_to_string("./hello1.txt").unwrap(),"mine");
+    assert_eq!(master.borrow().commitpointer,before);
+    std::fs::remove_file("./hello1.txt").unwrap();
+
+    //其他文件上暂存和未暂存的改动在快进后保留
+    std::fs::write("./new.txt","new").unwrap();
+    gitAdd(&mut repo,"./new.txt".to_string(),false).unwrap();
+    std::fs::write("./hello.txt","edited").unwrap();
+    let result=gitMerge(&mut repo,String::from("b1"),"ff","alex",MergeMode::FastForward,false).unwrap();
+    assert_eq!(result.outcome,MergeOutcome::FastForward);
+    assert_eq!(master.borrow().commitpointer,b1.borrow().commitpointer);
+    assert!(repo.head.index.contains("./new.txt") && repo.head.index.contains("./hello1.txt"));
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"edited");
+    assert!(repo.head.index.isModified("./hello.txt").unwrap());
+    std::fs::write("./hello.txt","hello0").unwrap();
+    assert_eq!(master.borrow().references.borrow().refermap.get("./hello1.txt"),b1.borrow().references.borrow().refermap.get("./hello1.txt"));
+
+    let result=gitMerge(&mut repo,String::from("b1"),"again","alex",MergeMode::FastForward,false).unwrap();
+    assert_eq!(result.outcome,MergeOutcome::UpToDate);
+
+    //分叉之后不能快进
+    gitAdd(&mut repo,"./hello2.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_second","alex",false).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b1_second","alex",false).unwrap();
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert!(matches!(gitMerge(&mut repo,String::from("b1"),"ffonly","alex",MergeMode::FastForwardOnly,false),Err(GitcError::NonFastForward(_))));
+
+    //--no-ff在可以快进时也生成merge commit
+    gitBranch(&mut repo,String::from("b2"),false).unwrap();
+    gitCheckout(&mut repo,String::from("b2"),false,false).unwrap();
+    gitAdd(&mut repo,"./demo/demo2.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b2_first","alex",false).unwrap();
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    let result=gitMerge(&mut repo,String::from("b2"),"noff","alex",MergeMode::NoFastForward,false).unwrap();
+    assert_eq!(result.outcome,MergeOutcome::Merged);
+    let commit=tipCommit(&repo.head,&repo.objects,"master");
+    assert_eq!(commit.borrow().message,"noff");
+    assert_eq!(commit.borrow().directParents().len(),2);
+}
+
+#[test]
+fn checkout_test() {
+    let _guard=sandbox("checkout_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    std::fs::write("./hello.txt","b1").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b1_first","alex",false).unwrap();
+    std::fs::remove_file("./demo/demo2.txt").unwrap();
+
+    //切回master时b1独有的文件和变空的文件夹都被删除，改过的文件恢复成master的版本
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert_eq!(repo.head.currentBranchName,"master");
+    assert!(!Path::new("./demo").exists());
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"hello0");
+
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    assert_eq!(std::fs::read_to_string("./demo/demo1.txt").unwrap(),"demo1");
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"b1");
+
+    //没有提交的改动会被覆盖时拒绝切换，--force则直接丢弃
+    std::fs::write("./hello.txt","dirty").unwrap();
+    assert!(matches!(gitCheckout(&mut repo,String::from("master"),false,false),Err(GitcError::WouldOverwrite(operation,paths)) if operation=="checkout" && paths==vec![String::from("./hello.txt")]));
+    assert_eq!(repo.head.currentBranchName,"b1");
+    gitCheckout(&mut repo,String::from("master"),true,false).unwrap();
+    assert_eq!(repo.head.currentBranchName,"master");
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"hello0");
+
+    //两个分支中相同的文件上的改动会被保留
+    std::fs::write("./hello1.txt","untracked").unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    assert_eq!(std::fs::read_to_string("./hello1.txt").unwrap(),"untracked");
+}
+
+#[test]
+fn status_test() {
+    let _guard=sandbox("status_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./hello2.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+    assert!(statusEntries(&repo).unwrap().iter().all(|entry| entry.unstaged==Some(FileStatus::Untracked)));
+
+    std::fs::write("./hello.txt","changed").unwrap();
+    std::fs::write("./hello1.txt","staged").unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    std::fs::write("./hello1.txt","staged and changed").unwrap();
+    std::fs::remove_file("./hello2.txt").unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+
+    let lines:Vec<String>=statusEntries(&repo).unwrap().iter().map(|entry| entry.porcelain()).collect();
+    assert_eq!(lines,vec![
+        "A  ./demo/demo1.txt",
+        "?? ./demo/demo2.txt",
+        " M ./hello.txt",
+        "MM ./hello1.txt",
+        " D ./hello2.txt",
+    ]);
+}
+
+#[test]
+fn diff_test() {
+    let old=b"a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let new=b"a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk";
+    assert_eq!(unifiedHunks(old,new,3,DiffAlgorithm::Myers),"@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -8,3 +8,4 @@\n h\n i\n j\n+k\n\\ No newline at end of file\n");
+    assert_eq!(unifiedHunks(old,new,5,DiffAlgorithm::Myers).matches("@@ -").count(),1);
+    assert_eq!(unifiedHunks(old,old,3,DiffAlgorithm::Myers),"");
+
+    //三种算法得到的DiffOp都能从旧内容还原出新内容
+    let a=splitLines(b"x\n}\nfn a\n}\nfn b\n}\n");
+    let b=splitLines(b"x\n}\nfn b\n}\nfn c\n}\nfn a\n}\n");
+    for algorithm in [DiffAlgorithm::Myers,DiffAlgorithm::Patience,DiffAlgorithm::Histogram]
+    {
+        let mut rebuilt:Vec<&[u8]>=vec![];
+        for op in diff(algorithm,&a,&b)
+        {
+            match op
+            {
+                DiffOp::Equal{old,len,..}=>rebuilt.extend(&a[old..old+len]),
+                DiffOp::Insert{new,len}=>rebuilt.extend(&b[new..new+len]),
+                DiffOp::Delete{..}=>{}
+            }
+        }
+        assert_eq!(rebuilt,b);
+    }
+
+    //每一行都不同的大文件：Myers只使用线性的内存
+    let a:Vec<String>=(0..6000).map(|i| format!("old {}\n",i)).collect();
+    let b:Vec<String>=(0..6000).map(|i| format!("new {}\n",i)).collect();
+    assert_eq!(diff(DiffAlgorithm::Myers,&a,&b),vec![DiffOp::Delete{old:0,len:6000},DiffOp::Insert{new:0,len:6000}]);
+
+    let _guard=sandbox("diff_test");
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+    let first=repo.objects.commitmap.keys().next().unwrap().clone();
+
+    std::fs::write("./hello.txt","hello0\nhello3\n").unwrap();
+    let options=DiffOptions::default();
+    assert_eq!(diffText(&repo,&DiffMode::WorkingTree,&options).unwrap(),
+        "diff --git a/hello.txt b/hello.txt\n--- a/hello.txt\n+++ b/hello.txt\n@@ -1 +1,2 @@\n-hello0\n\\ No newline at end of file\n+hello0\n+hello3\n");
+    assert_eq!(diffText(&repo,&DiffMode::Cached,&options).unwrap(),"");
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    std::fs::write("./hello1.txt",[0u8,1,2]).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    assert_eq!(diffText(&repo,&DiffMode::WorkingTree,&options).unwrap(),"");
+    let cached=diffText(&repo,&DiffMode::Cached,&options).unwrap();
+    assert!(cached.contains("+hello3\n"));
+    assert!(cached.contains("new file mode 100644\nBinary files /dev/null and b/hello1.txt differ\n"));
+
+    gitCommit(&mut repo,"master_second","alex",false).unwrap();
+    let commits=diffText(&repo,&DiffMode::Commits(first,String::from("master")),&options).unwrap();
+    assert_eq!(commits,cached);
+}
+
+#[test]
+fn log_test() {
+    let _guard=sandbox("log_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_first","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"master_second","alex",false).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    gitAdd(&mut repo,"./hello2.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b1_first","bob",false).unwrap();
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    gitMerge(&mut repo,String::from("b1"),"merge b1","alex",MergeMode::FastForward,false).unwrap();
+
+    let short=|message:&str|
+    {
+        let commit=repo.objects.commitmap.values().find(|commit| commit.borrow().message==message).unwrap();
+        commitId(commit).unwrap()[..7].to_string()
+    };
+    let options=LogOptions{oneline:true,graph:true,..LogOptions::default()};
+    assert_eq!(logLines(&repo,&options).unwrap(),vec![
+        format!("* {} merge b1",short("merge b1")),
+        String::from("|\\"),
+        format!("* | {} master_second",short("master_second")),
+        format!("| * {} b1_first",short("b1_first")),
+        String::from("|/"),
+        format!("* {} master_first",short("master_first")),
+    ]);
+
+    let messages=|options:LogOptions|->Vec<String>
+    {
+        logLines(&repo,&LogOptions{oneline:true,..options}).unwrap().iter().map(|line| line[8..].to_string()).collect()
+    };
+    assert_eq!(messages(LogOptions{maxCount:Some(2),..LogOptions::default()}),vec!["merge b1","master_second"]);
+    assert_eq!(messages(LogOptions{author:Some(String::from("bob")),..LogOptions::default()}),vec!["b1_first"]);
+    assert_eq!(messages(LogOptions{grep:Some(String::from("master")),..LogOptions::default()}),vec!["master_second","master_first"]);
+    assert_eq!(messages(LogOptions{path:Some(String::from("./hello2.txt")),..LogOptions::default()}),vec!["b1_first"]);
+    assert_eq!(messages(LogOptions{start:Some(String::from("b1")),..LogOptions::default()}),vec!["b1_first","master_first"]);
+
+    let full=logLines(&repo,&LogOptions::default()).unwrap();
+    assert_eq!(full[1],format!("Merge: {} {}",short("master_second"),short("b1_first")));
+    assert_eq!(full[2],"Author: alex <>");
+    assert_eq!(full[3],"Date:   Thu Jan 1 00:00:00 1970 +0000");
+    assert_eq!(full[5],"    merge b1");
+}
+
+#[test]
+fn hash_test() {
+    let _guard=sandbox("hash_test");
+
+    //和git hash-object、git write-tree的结果相同，与文件所在的路径无关
+    assert_eq!(hashBlob(b"hello0"),"b04bfec0d64fe8d2492f4f05990517801fa5cc3e");
+    assert_eq!(getSHA1(b""),"da39a3ee5e6b4b0d3255bfef95601890afd80709");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"first","alex",false).unwrap();
+
+    let reference=repo.head.currentBranch().unwrap().borrow().references.clone();
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().id,"b04bfec0d64fe8d2492f4f05990517801fa5cc3e");
+    assert_eq!(tipCommit(&repo.head,&repo.objects,"master").borrow().snapshot,"a273eac28d75802dd5cb890ced43b14adb27c64d");
+    assert!(repo.objects.commitmap.contains_key("9cd8cbf096883446ba4b26f14c9ef59604badc95"));
+    assert!(Path::new("./.gitc/objects/b0/4bfec0d64fe8d2492f4f05990517801fa5cc3e").is_file());
+
+    //旧版本存储中的SHA1在读取时被重新计算，references中暂存的文件被移到暂存区
+    let mut old=LegacyObjects{treemap:BTreeMap::new(),commitmap:BTreeMap::new(),blobmap:BTreeMap::new()};
+    for (id,file) in repo.objects.blobmap.iter()
+    {
+        old.blobmap.insert(format!("old-{}",id),file.clone());
+    }
+    for (id,node) in repo.objects.treemap.iter()
+    {
+        old.treemap.insert(format!("old-{}",id),legacyTree(&repo.objects,id,&node.borrow().name));
+    }
+    for id in repo.objects.commitmap.keys()
+    {
+        old.commitmap.insert(format!("old-{}",id),legacyCommit(&repo.objects,id));
+    }
+    for (path,id) in repo.head.index.entries.iter()
+    {
+        reference.borrow_mut().refermap.insert(path.clone(),id.id.clone());
+    }
+    reference.borrow_mut().refermap.insert(String::from("."),String::from("a273eac28d75802dd5cb890ced43b14adb27c64d"));
+    reference.borrow_mut().refermap.insert(String::from("first"),String::from("9cd8cbf096883446ba4b26f14c9ef59604badc95"));
+    for id in reference.borrow_mut().refermap.values_mut()
+    {
+        *id=format!("old-{}",id);
+    }
+    std::fs::write("./.gitc/maps/objs",bincode::serialize(&old).unwrap()).unwrap();
+    std::fs::write("./.gitc/maps/head",bincode::serialize(&legacyHead(&repo.head,&repo.objects)).unwrap()).unwrap();
+    std::fs::remove_file("./.gitc/maps/version").unwrap();
+    std::fs::remove_dir_all("./.gitc/objects").unwrap();
+    let (migrated,loaded)=loadState(Path::new(".")).unwrap();
+    for id in repo.objects.blobmap.keys().chain(repo.objects.treemap.keys()).chain(repo.objects.commitmap.keys())
+    {
+        assert!(migrated.contains(id));
+    }
+    assert!(!Path::new("./.gitc/maps/objs").exists());
+    assert!(loaded.currentBranch().unwrap().borrow().references.borrow().refermap.is_empty());
+    assert_eq!(loaded.currentBranch().unwrap().borrow().commitpointer.clone().unwrap(),"9cd8cbf096883446ba4b26f14c9ef59604badc95");
+    assert_eq!(loaded.index.get("./hello.txt").unwrap().id,"b04bfec0d64fe8d2492f4f05990517801fa5cc3e");
+    assert_eq!(loaded.index.entries.keys().collect::<Vec<_>>(),vec!["./demo/demo1.txt","./hello.txt"]);
+
+    let mut repo=Repository::new(loaded,migrated);
+
+    //内容相同的文件夹共用同一个tree，之后在其中一个里添加文件不会影响另一个
+    std::fs::create_dir("./copy").unwrap();
+    std::fs::write("./copy/demo1.txt","demo1").unwrap();
+    gitAdd(&mut repo,"./copy/demo1.txt".to_string(),false).unwrap();
+    let root=buildSnapshot(&repo.head.index.blobs(),&mut repo.objects).unwrap();
+    let snapshot=repo.objects.loadTree(&root,".").unwrap();
+    assert_eq!(snapshot.borrow().trees["./copy"],snapshot.borrow().trees["./demo"]);
+    std::fs::write("./copy/new.txt","new").unwrap();
+    gitAdd(&mut repo,"./copy/new.txt".to_string(),false).unwrap();
+    let root=buildSnapshot(&repo.head.index.blobs(),&mut repo.objects).unwrap();
+    let files:Vec<String>=snapshotBlobs(&repo.objects,&root).unwrap().into_keys().collect();
+    assert_eq!(files,vec!["./copy/demo1.txt","./copy/new.txt","./demo/demo1.txt","./hello.txt"]);
+}
+
+#[test]
+fn object_store_test() {
+    let _guard=sandbox("object_store_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"first","alex",true).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"second","bob",true).unwrap();
+    let tipId=repo.head.currentBranch().unwrap().borrow().commitpointer.clone().unwrap();
+
+    //重新读取时缓存为空，对象按SHA1从./.gitc/objects中读出，内容和SHA1都不变
+    let (cache,_)=loadState(Path::new(".")).unwrap();
+    assert!(cache.commitmap.is_empty() && cache.blobmap.is_empty());
+    assert_eq!(cache.kindOf(&tipId),Some(gitc::gitUtils::gitUtils::objecttype::commit));
+    let loaded=cache.loadCommit(&tipId).unwrap();
+    assert_eq!(commitId(&loaded).unwrap(),tipId);
+    assert_eq!(loaded.borrow().author.name,"bob");
+    let parents=loaded.borrow().directParents();
+    assert_eq!(parents.len(),1);
+    assert_eq!(cache.loadCommit(&parents[0]).unwrap().borrow().message,"first");
+    let files:Vec<String>=snapshotBlobs(&cache,&loaded.borrow().snapshot).unwrap().into_keys().collect();
+    assert_eq!(files,vec!["./demo/demo1.txt","./hello.txt","./hello1.txt"]);
+    assert_eq!(cache.loadBlob(&hashBlob(b"demo1"),"./demo/demo1.txt").unwrap().borrow().contents,b"demo1");
+    assert!(matches!(cache.loadCommit(&hashBlob(b"demo1")),Err(GitcError::CorruptStore(_))));
+    assert!(matches!(cache.loadBlob(&hashBlob(b"missing"),"./missing"),Err(GitcError::ObjectNotFound(_))));
+    assert!(!Path::new("./.gitc/maps/objs").exists());
+
+    //对象以zlib压缩后存放，和git的loose object相同
+    let mut stored=vec![];
+    flate2::read::ZlibDecoder::new(File::open("./.gitc/objects/b0/4bfec0d64fe8d2492f4f05990517801fa5cc3e").unwrap()).read_to_end(&mut stored).unwrap();
+    assert_eq!(stored,b"blob 6\0hello0");
+    std::fs::write("./big.txt","gitc\n".repeat(4096)).unwrap();
+    let before=storeStats(Path::new(".")).unwrap();
+    gitAdd(&mut repo,"./big.txt".to_string(),true).unwrap();
+    let after=storeStats(Path::new(".")).unwrap();
+    assert!(after.rawSize-before.rawSize>5*4096);
+    assert!(after.compressedSize-before.compressedSize<1024);
+    assert_eq!(cache.loadBlob(&hashBlob("gitc\n".repeat(4096).as_bytes()),"./big.txt").unwrap().borrow().contents.len(),5*4096);
+}
+
+#[test]
+fn pack_test() {
+    let _guard=sandbox("pack_test");
+    use gitc::gitUtils::pack::{createDelta,applyDelta,packFiles};
+    use gitc::gitUtils::objectStore::{readObject,isLoose};
+    use gitc::gitMethods::gitRepack::looseIds;
+
+    //delta的copy和insert指令可以还原出目标内容
+    let base:Vec<u8>=(0..3000).flat_map(|i| format!("line {} {}\n",i,getSHA1(&[i as u8,(i>>8) as u8])).into_bytes()).collect();
+    let mut target=base.clone();
+    target.splice(100..110,b"changed".iter().copied());
+    target.extend_from_slice(b"tail\n");
+    let delta=createDelta(&base,&target);
+    assert!(delta.len()<100);
+    assert_eq!(applyDelta(&base,&delta).unwrap(),target);
+    assert_eq!(applyDelta(b"",&createDelta(b"","abc".as_bytes())).unwrap(),b"abc");
+    assert!(matches!(applyDelta(b"x",&delta),Err(GitcError::CorruptStore(_))));
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let mut versions=vec![];
+    let mut contents=base.clone();
+    for i in 0..5
+    {
+        contents.extend_from_slice(format!("version {}\n",i).as_bytes());
+        std::fs::write("./big.txt",&contents).unwrap();
+        versions.push(hashBlob(&contents));
+        gitAdd(&mut repo,"./big.txt".to_string(),true).unwrap();
+        gitCommit(&mut repo,&format!("v{}",i),"alex",true).unwrap();
+    }
+    let tipId=repo.head.currentBranch().unwrap().borrow().commitpointer.clone().unwrap();
+    let before=storeStats(Path::new(".")).unwrap();
+
+    //repack之后没有loose object，相似的big.txt只保存一份完整内容，其余都是delta
+    let summary=gitRepack(&repo).unwrap();
+    assert_eq!(summary.objects,before.count);
+    assert!(summary.deltas>=4);
+    assert!(looseIds(Path::new(".")).unwrap().is_empty());
+    assert_eq!(packFiles(Path::new(".")).unwrap().len(),1);
+    let after=storeStats(Path::new(".")).unwrap();
+    assert_eq!(after.count,before.count);
+    assert_eq!(after.rawSize,before.rawSize);
+    assert!(after.compressedSize*2<before.compressedSize);
+
+    //pack中的对象和loose object一样按SHA1读取
+    let (cache,state)=loadState(Path::new(".")).unwrap();
+    for (i,id) in versions.iter().enumerate()
+    {
+        assert!(!isLoose(Path::new("."),id) && cache.contains(id));
+        assert!(readObject(Path::new("."),id).unwrap().1.ends_with(format!("version {}\n",i).as_bytes()));
+    }
+    let files=state.index.files();
+    assert_eq!(cache.blobContents(&files["./big.txt"]).unwrap(),contents);
+    assert_eq!(files.into_keys().collect::<Vec<_>>(),vec!["./big.txt"]);
+    assert_eq!(cache.loadCommit(&tipId).unwrap().borrow().message,"v4");
+    //pack的index只读一次，delta链上的对象也通过缓存的index读取
+    let packs=cache.packs().unwrap();
+    assert!(Rc::ptr_eq(&packs,&cache.packs().unwrap()));
+    assert_eq!(packs[0].path,packFiles(Path::new(".")).unwrap()[0]);
+    assert_eq!(cache.readObject(&versions[0]).unwrap().1,readObject(Path::new("."),&versions[0]).unwrap().1);
+
+    //新写入的loose object在下一次repack时和旧的pack合并
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    assert!(isLoose(Path::new("."),&hashBlob(b"hello0")));
+    assert_eq!(gitRepack(&repo).unwrap().objects,before.count+1);
+    assert_eq!(packFiles(Path::new(".")).unwrap().len(),1);
+    assert!(looseIds(Path::new(".")).unwrap().is_empty());
+    assert_eq!(readObject(Path::new("."),&hashBlob(b"hello0")).unwrap(),(String::from("blob"),b"hello0".to_vec()));
+    //repack之后仓库中缓存的index指向新的pack
+    assert_eq!(repo.objects.packs().unwrap()[0].path.file_name(),packFiles(Path::new(".")).unwrap()[0].file_name());
+    assert_eq!(repo.objects.readObject(&hashBlob(b"hello0")).unwrap().1,b"hello0");
+    assert_eq!(gitRepack(&repo).unwrap().objects,before.count+1);
+    assert_eq!(packFiles(Path::new(".")).unwrap().len(),1);
+}
+
+#[test]
+fn gc_test() {
+    let _guard=sandbox("gc_test");
+    use gitc::gitMethods::gitGc::{gitGc,parsePrune,DEFAULT_PRUNE};
+    use gitc::gitUtils::objectStore::{writeObject,objectPath,hasObject};
+    use std::time::{Duration,SystemTime};
+
+    assert_eq!(parsePrune("now"),Some(Some(Duration::ZERO)));
+    assert_eq!(parsePrune("never"),Some(None));
+    assert_eq!(parsePrune("2.weeks.ago"),Some(Some(DEFAULT_PRUNE)));
+    assert_eq!(parsePrune("3.days"),Some(Some(Duration::from_secs(3*24*60*60))));
+    assert_eq!(parsePrune("soon"),None);
+    assert_eq!(parsePrune("99999999999999999.weeks.ago"),None);
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"first","alex",true).unwrap();
+    //覆盖暂存的版本之后，中间暂存过的blob不再被任何地方引用
+    std::fs::write("./hello.txt","changed").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    let orphan=hashBlob(b"changed");
+    std::fs::write("./hello.txt","changed again").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    assert!(repo.objects.blobmap.contains_key(&orphan));
+
+    //tag和reflog中的对象也会保留
+    let tagged=writeObject(Path::new("."),"blob",b"tagged").unwrap();
+    std::fs::write("./.gitc/refs/tags/v1",format!("{}\n",tagged)).unwrap();
+    let logged=writeObject(Path::new("."),"blob",b"logged").unwrap();
+    std::fs::create_dir_all("./.gitc/logs").unwrap();
+    std::fs::write("./.gitc/logs/HEAD",format!("{} {} alex <> 0 +0000\tcommit: logged\n","0".repeat(40),logged)).unwrap();
+
+    //还在保留期内的对象不会被删除
+    let summary=gitGc(&mut repo,Some(DEFAULT_PRUNE)).unwrap();
+    assert_eq!(summary.pruned,0);
+    assert_eq!(summary.kept,1);
+    assert!(hasObject(Path::new("."),&orphan));
+    assert!(!repo.objects.blobmap.contains_key(&orphan));
+    let old=SystemTime::now()-Duration::from_secs(30*24*60*60);
+    File::options().write(true).open(objectPath(Path::new("."),&orphan)).unwrap().set_modified(old).unwrap();
+    let summary=gitGc(&mut repo,Some(DEFAULT_PRUNE)).unwrap();
+    assert_eq!(summary.pruned,1);
+    assert!(!hasObject(Path::new("."),&orphan));
+    let fresh=writeObject(Path::new("."),"blob",b"fresh").unwrap();
+    assert_eq!(gitGc(&mut repo,None).unwrap().pruned,0);
+
+    let summary=gitGc(&mut repo,Some(Duration::ZERO)).unwrap();
+    assert_eq!(summary.pruned,1);
+    assert_eq!(summary.kept,0);
+    assert!(!hasObject(Path::new("."),&fresh));
+    assert!(hasObject(Path::new("."),&tagged) && hasObject(Path::new("."),&logged));
+    let (cache,state)=loadState(Path::new(".")).unwrap();
+    assert_eq!(cache.blobContents(&state.index.files()["./hello.txt"]).unwrap(),b"changed again");
+    let files:Vec<String>=commitContents(&cache,&tipCommit(&state,&cache,"master")).into_keys().collect();
+    assert_eq!(files,vec!["./hello.txt"]);
+
+    //pack中没有被引用的对象在重写pack时删除
+    gitRepack(&repo).unwrap();
+    std::fs::write("./hello.txt","packed orphan").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    std::fs::write("./hello.txt","final").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    gitRepack(&repo).unwrap();
+    assert!(hasObject(Path::new("."),&hashBlob(b"packed orphan")));
+    let summary=gitGc(&mut repo,Some(Duration::ZERO)).unwrap();
+    assert!(summary.pruned>=1);
+    assert!(!hasObject(Path::new("."),&hashBlob(b"packed orphan")));
+    assert!(hasObject(Path::new("."),&tagged) && hasObject(Path::new("."),&hashBlob(b"hello0")) && hasObject(Path::new("."),&hashBlob(b"final")));
+    let packs=gitc::gitUtils::pack::packFiles(Path::new(".")).unwrap();
+    assert_eq!(packs.len(),1);
+    //verifyPack在新的pack缺少要保留的对象时报错，gitGc和gitRepack都是在它之后才删除旧的pack
+    let kept=std::collections::BTreeSet::from([hashBlob(b"final"),hashBlob(b"not packed")]);
+    assert!(matches!(gitc::gitUtils::pack::verifyPack(&packs[0],&kept),Err(GitcError::CorruptStore(_))));
+    assert!(gitc::gitUtils::pack::verifyPack(&packs[0],&std::collections::BTreeSet::from([hashBlob(b"final")])).is_ok());
+}
+
+#[test]
+fn rm_staged_test() {
+    let _guard=sandbox("rm_staged_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    for path in ["./hello.txt","./hello1.txt","./demo/demo1.txt","./demo/demo2.txt"]
+    {
+        gitAdd(&mut repo,path.to_string(),false).unwrap();
+    }
+    gitCommit(&mut repo,"first","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("other"),false).unwrap();
+    let firstId=repo.head.currentBranch().unwrap().borrow().commitpointer.clone().unwrap();
+    let first=repo.objects.loadCommit(&firstId).unwrap();
+
+    //工作区或暂存区中有没提交的改动时拒绝删除，文件和暂存的版本都不变
+    std::fs::write("./hello.txt","changed").unwrap();
+    assert!(matches!(gitRm(&mut repo,"./hello.txt".to_string(),false,false,false,false),Err(GitcError::LocalModifications(paths)) if paths==vec![String::from("./hello.txt")]));
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    assert!(matches!(gitRm(&mut repo,"./hello.txt".to_string(),false,false,false,false),Err(GitcError::LocalModifications(_))));
+    assert_eq!(std::fs::read_to_string("./hello.txt").unwrap(),"changed");
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().id,hashBlob(b"changed"));
+    //--cached保留工作区的文件，只有暂存的版本和HEAD、工作区都不同时才拒绝
+    std::fs::write("./hello.txt","changed again").unwrap();
+    assert!(matches!(gitRm(&mut repo,"./hello.txt".to_string(),true,false,false,false),Err(GitcError::LocalModifications(_))));
+    std::fs::write("./hello.txt","hello0").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+
+    //默认同时删除工作区中的文件，blob仍然留给之前的commit和其他分支
+    gitRm(&mut repo,"./hello.txt".to_string(),false,false,false,false).unwrap();
+    assert!(!Path::new("./hello.txt").exists());
+    assert!(!repo.head.index.contains("./hello.txt"));
+    assert!(repo.objects.contains(&hashBlob(b"hello0")));
+    assert_eq!(commitId(&first).unwrap(),firstId);
+    assert_eq!(snapshotBlobs(&repo.objects,&first.borrow().snapshot).unwrap().len(),4);
+    assert_eq!(commitContents(&repo.objects,&tipCommit(&repo.head,&repo.objects,"other"))["./hello.txt"],b"hello0");
+
+    //--cached只删除暂存的版本，已经从工作区删除的文件也可以rm
+    gitRm(&mut repo,"./hello1.txt".to_string(),true,false,false,false).unwrap();
+    assert!(Path::new("./hello1.txt").is_file());
+    std::fs::remove_file("./demo/demo2.txt").unwrap();
+    gitRm(&mut repo,"./demo/demo2.txt".to_string(),false,false,false,false).unwrap();
+    assert_eq!(repo.head.index.entries.keys().collect::<Vec<_>>(),vec!["./demo/demo1.txt"]);
+
+    //文件夹需要-r，删除后空的文件夹也不再出现在树中
+    assert!(matches!(gitRm(&mut repo,"./demo".to_string(),false,false,false,false),Err(GitcError::RecursiveRequired(_))));
+    gitRm(&mut repo,"./demo".to_string(),false,true,false,false).unwrap();
+    assert!(!Path::new("./demo").exists());
+    assert!(repo.head.index.entries.is_empty());
+    let root=buildSnapshot(&repo.head.index.blobs(),&mut repo.objects).unwrap();
+    assert!(repo.objects.loadTree(&root,".").unwrap().borrow().trees.is_empty());
+    let statuses:Vec<String>=statusEntries(&repo).unwrap().iter().map(|entry| entry.porcelain()).collect();
+    assert_eq!(statuses,vec!["D  ./demo/demo1.txt","D  ./demo/demo2.txt","D  ./hello.txt","D  ./hello1.txt","?? ./hello1.txt","?? ./hello2.txt"]);
+
+    gitCommit(&mut repo,"second","alex",false).unwrap();
+    assert!(commitContents(&repo.objects,&tipCommit(&repo.head,&repo.objects,"master")).is_empty());
+    assert_eq!(commitContents(&repo.objects,&tipCommit(&repo.head,&repo.objects,"other")).len(),4);
+}
+
+#[test]
+fn add_pathspec_test() {
+    let _guard=sandbox("add_pathspec_test");
+    use gitc::gitUtils::wildmatch::wildmatch;
+
+    assert!(wildmatch("./*.rs","./src/main.rs",false));
+    assert!(!wildmatch("*.rs","src/main.rs",true));
+    assert!(wildmatch("src/**","src/a/b.rs",true));
+    assert!(wildmatch("**/b.rs","b.rs",true) && wildmatch("a/**/b","a/x/y/b",true) && wildmatch("a/**/b","a/b",true));
+    assert!(wildmatch("hello[0-9].txt","hello1.txt",true) && !wildmatch("hello[!0-9].txt","hello1.txt",true));
+    assert!(wildmatch("h?llo","hello",true) && !wildmatch("a?b","a/b",true) && wildmatch("[a","[a",true));
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let staged=|head:&HEAD| -> Vec<String> {
+        head.index.entries.keys().cloned().collect()
+    };
+
+    //文件夹会递归暂存其中的所有文件
+    gitAdd(&mut repo,"./demo".to_string(),false).unwrap();
+    assert_eq!(staged(&repo.head),vec!["./demo/demo1.txt","./demo/demo2.txt"]);
+    let added=gitAddPathspecs(&mut repo,&[String::from("./hello[12].txt")],AddMode::Default,false).unwrap();
+    assert_eq!(added,vec!["./hello1.txt","./hello2.txt"]);
+    assert!(matches!(gitAddPathspecs(&mut repo,&[String::from("./*.rs")],AddMode::Default,false),Err(GitcError::PathNotFound(_))));
+
+    //-u只更新已经暂存的文件，包括删除，未暂存的hello.txt不会加入
+    std::fs::write("./demo/demo1.txt","changed").unwrap();
+    std::fs::remove_file("./hello2.txt").unwrap();
+    let added=gitAddPathspecs(&mut repo,&[],AddMode::Update,false).unwrap();
+    assert_eq!(added,vec!["./demo/demo1.txt","./hello2.txt"]);
+    assert_eq!(staged(&repo.head),vec!["./demo/demo1.txt","./demo/demo2.txt","./hello1.txt"]);
+    assert_eq!(repo.head.index.get("./demo/demo1.txt").unwrap().id,hashBlob(b"changed"));
+
+    //-A暂存所有新文件和删除
+    std::fs::create_dir_all("./src/a").unwrap();
+    std::fs::write("./src/a/b.rs","fn b(){}").unwrap();
+    std::fs::remove_file("./demo/demo2.txt").unwrap();
+    gitAddPathspecs(&mut repo,&[String::from("./src/**")],AddMode::Default,false).unwrap();
+    assert!(repo.head.index.contains("./src/a/b.rs"));
+    gitAddPathspecs(&mut repo,&[],AddMode::All,false).unwrap();
+    assert_eq!(staged(&repo.head),vec!["./demo/demo1.txt","./hello.txt","./hello1.txt","./src/a/b.rs"]);
+    assert!(statusEntries(&repo).unwrap().iter().all(|entry| entry.unstaged.is_none()));
+}
+
+#[test]
+fn ignore_test() {
+    let _guard=sandbox("ignore_test");
+    use gitc::gitUtils::ignore::IgnoreRules;
+    use gitc::gitMethods::gitIgnore::{cleanFiles,checkIgnore,CleanOptions};
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    //已经暂存的文件不受忽略规则影响
+    gitAdd(&mut repo,"./demo/demo2.txt".to_string(),false).unwrap();
+
+    std::fs::write("./.gitcignore","# build output\n*.log\n!keep.log\ntarget/\n!target/keep.txt\n/root.txt\nbuild/**/*.o\n").unwrap();
+    std::fs::write("./demo/.gitcignore","demo2.txt\n").unwrap();
+    std::fs::create_dir_all("./.gitc/info").unwrap();
+    std::fs::write("./.gitc/info/exclude","*.swp\n").unwrap();
+    for file in ["./a.log","./keep.log","./target/out.bin","./target/keep.txt","./root.txt","./demo/root.txt","./build/x/y/z.o","./build/z.o","./x.swp"]
+    {
+        std::fs::create_dir_all(Path::new(file).parent().unwrap()).unwrap();
+        std::fs::write(file,file).unwrap();
+    }
+    let rules=IgnoreRules::load(Path::new(".")).unwrap();
+    let ignored:Vec<&str>=["./a.log","./keep.log","./target/out.bin","./target/keep.txt","./root.txt","./demo/root.txt","./build/x/y/z.o","./build/z.o","./x.swp","./demo/demo2.txt","./hello.txt"]
+        .into_iter().filter(|path| rules.isIgnored(path,false)).collect();
+    assert_eq!(ignored,vec!["./a.log","./target/out.bin","./target/keep.txt","./root.txt","./build/x/y/z.o","./build/z.o","./x.swp","./demo/demo2.txt"]);
+    assert!(rules.isIgnored("./target",true) && !rules.isIgnored("./target",false));
+
+    //check-ignore给出匹配的规则，包括"!"规则
+    let matched=checkIgnore(&repo,&[String::from("./a.log"),String::from("./keep.log"),String::from("./demo/demo2.txt"),String::from("./hello.txt")]).unwrap();
+    let rule=matched[0].1.clone().unwrap();
+    assert_eq!((rule.source.as_str(),rule.line,rule.text.as_str(),rule.negated),(".gitcignore",2,"*.log",false));
+    assert!(matched[1].1.as_ref().unwrap().negated);
+    assert_eq!(matched[2].1.as_ref().unwrap().source,"demo/.gitcignore");
+    assert!(matched[3].1.is_none());
+
+    //add和status都跳过被忽略的文件
+    assert!(matches!(gitAdd(&mut repo,"./a.log".to_string(),false),Err(GitcError::PathIgnored(_))));
+    assert_eq!(gitAddPathspecs(&mut repo,&[String::from("./*.log")],AddMode::Default,false).unwrap(),vec!["./keep.log"]);
+    assert!(matches!(gitAddPathspecs(&mut repo,&[String::from("./target")],AddMode::Default,false),Err(GitcError::PathIgnored(_))));
+    std::fs::write("./demo/demo2.txt","changed").unwrap();
+    gitAddPathspecs(&mut repo,&[],AddMode::All,false).unwrap();
+    let files:Vec<&String>=repo.head.index.entries.keys().collect();
+    assert_eq!(files,vec!["./.gitcignore","./demo/.gitcignore","./demo/demo1.txt","./demo/demo2.txt","./demo/root.txt","./hello.txt","./hello1.txt","./hello2.txt","./keep.log"]);
+    assert_eq!(repo.head.index.get("./demo/demo2.txt").unwrap().id,hashBlob(b"changed"));
+    std::fs::write("./new.txt","new").unwrap();
+    let statuses:Vec<String>=statusEntries(&repo).unwrap().iter().filter(|entry| entry.unstaged.is_some()).map(|entry| entry.porcelain()).collect();
+    assert_eq!(statuses,vec!["?? ./new.txt"]);
+
+    //clean默认只删除已跟踪文件夹中没有暂存也没有被忽略的文件
+    std::fs::create_dir_all("./newdir").unwrap();
+    std::fs::write("./newdir/f.txt","f").unwrap();
+    let dryRun=CleanOptions{dryRun:true,..CleanOptions::default()};
+    assert_eq!(cleanFiles(&repo,&dryRun).unwrap(),vec!["./new.txt"]);
+    assert_eq!(cleanFiles(&repo,&CleanOptions{directories:true,..dryRun}).unwrap(),vec!["./new.txt","./newdir/f.txt"]);
+    assert_eq!(cleanFiles(&repo,&CleanOptions{onlyIgnored:true,..dryRun}).unwrap(),vec!["./a.log","./root.txt","./x.swp"]);
+    assert!(Path::new("./new.txt").exists());
+    let removed=cleanFiles(&repo,&CleanOptions{directories:true,ignored:true,..CleanOptions::default()}).unwrap();
+    assert_eq!(removed.len(),9);
+    assert!(!Path::new("./target").exists() && !Path::new("./newdir").exists() && !Path::new("./build").exists());
+    assert!(Path::new("./keep.log").exists() && Path::new("./demo/demo2.txt").exists());
+}
+
+#[test]
+fn readd_test() {
+    let _guard=sandbox("readd_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"first","alex",false).unwrap();
+    let rootId=tipCommit(&repo.head,&repo.objects,"master").borrow().snapshot.clone();
+
+    //改回之前的内容时blob已经存在，暂存的版本和父亲树仍然要更新
+    std::fs::write("./hello.txt","changed").unwrap();
+    std::fs::write("./demo/demo1.txt","changed").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    std::fs::write("./hello.txt","hello0").unwrap();
+    std::fs::write("./demo/demo1.txt","demo1").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().id,hashBlob(b"hello0"));
+    assert_eq!(repo.head.index.get("./demo/demo1.txt").unwrap().id,hashBlob(b"demo1"));
+    assert_eq!(buildSnapshot(&repo.head.index.blobs(),&mut repo.objects).unwrap(),rootId);
+    assert!(statusEntries(&repo).unwrap().iter().all(|entry| entry.staged.is_none()));
+
+    //内容和另一个已暂存文件相同的文件同样会暂存
+    std::fs::write("./hello1.txt","hello0").unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    let root=buildSnapshot(&repo.head.index.blobs(),&mut repo.objects).unwrap();
+    assert_eq!(snapshotBlobs(&repo.objects,&root).unwrap().get("./hello1.txt").unwrap(),&hashBlob(b"hello0"));
+}
+
+#[test]
+fn index_test() {
+    let _guard=sandbox("index_test");
+    use gitc::gitUtils::index::{INDEX_FILE,MODE_FILE};
+    use std::time::{Duration,SystemTime};
+
+    let mut repo=Repository::open(None).unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),true).unwrap();
+    assert!(Path::new(INDEX_FILE).is_file());
+    let entry=repo.head.index.get("./hello.txt").unwrap().clone();
+    assert_eq!((entry.id.as_str(),entry.mode,entry.size),("b04bfec0d64fe8d2492f4f05990517801fa5cc3e",MODE_FILE,6));
+    assert!(entry.mtime>0);
+    let (_,loaded)=loadState(Path::new(".")).unwrap();
+    assert_eq!(loaded.index,repo.head.index);
+
+    //文件信息和暂存时相同的文件不会重新计算SHA1，所以大小和修改时间不变的改动不会被发现
+    let old=SystemTime::now()-Duration::from_secs(60);
+    File::options().write(true).open("./hello.txt").unwrap().set_modified(old).unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    let mut loaded=Repository::open(None).unwrap();
+    std::fs::write("./hello.txt","hellox").unwrap();
+    File::options().write(true).open("./hello.txt").unwrap().set_modified(old).unwrap();
+    assert!(!loaded.head.index.isModified("./hello.txt").unwrap());
+    assert!(statusEntries(&loaded).unwrap().iter().all(|entry| entry.path!="./hello.txt" || entry.unstaged.is_none()));
+    std::fs::write("./hello.txt","changed").unwrap();
+    assert!(loaded.head.index.isModified("./hello.txt").unwrap());
+    let statuses:Vec<String>=statusEntries(&loaded).unwrap().iter().map(|entry| entry.porcelain()).collect();
+    assert!(statuses.contains(&String::from("AM ./hello.txt")));
+
+    //commit只使用暂存区中的版本，和工作区无关
+    std::fs::remove_file("./demo/demo1.txt").unwrap();
+    gitCommit(&mut loaded,"first","alex",true).unwrap();
+    let tip=tipCommit(&loaded.head,&loaded.objects,"master");
+    let files=commitContents(&loaded.objects,&tip);
+    assert_eq!(files.keys().collect::<Vec<_>>(),vec!["./demo/demo1.txt","./hello.txt"]);
+    assert_eq!(files["./hello.txt"],b"hello0");
+
+    //版本3的存储把暂存的文件和文件夹记录在当前分支的references中
+    let reference=loaded.head.currentBranch().unwrap().borrow().references.clone();
+    for (path,entry) in loaded.head.index.entries.iter()
+    {
+        reference.borrow_mut().refermap.insert(path.clone(),entry.id.clone());
+    }
+    reference.borrow_mut().refermap.insert(String::from("."),tip.borrow().snapshot.clone());
+    std::fs::write("./.gitc/maps/head",bincode::serialize(&legacyHead(&loaded.head,&loaded.objects)).unwrap()).unwrap();
+    std::fs::remove_file(INDEX_FILE).unwrap();
+    std::fs::write("./.gitc/maps/version","3").unwrap();
+    let (_,migrated)=loadState(Path::new(".")).unwrap();
+    assert_eq!(migrated.index.entries.keys().collect::<Vec<_>>(),vec!["./demo/demo1.txt","./hello.txt"]);
+    assert_eq!(migrated.index.get("./hello.txt").unwrap().id,hashBlob(b"hello0"));
+    assert!(migrated.currentBranch().unwrap().borrow().references.borrow().refermap.is_empty());
+    assert_eq!(std::fs::read_to_string("./.gitc/maps/version").unwrap(),"7");
+
+    //可执行文件在tree对象中的mode为100755，读回tree时保留
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        use gitc::gitUtils::index::MODE_EXECUTABLE;
+        std::fs::set_permissions("./hello.txt",std::fs::Permissions::from_mode(0o755)).unwrap();
+        gitAdd(&mut loaded,"./hello.txt".to_string(),false).unwrap();
+        assert_eq!(loaded.head.index.get("./hello.txt").unwrap().mode,MODE_EXECUTABLE);
+        gitCommit(&mut loaded,"executable","alex",false).unwrap();
+        let snapshot=tipCommit(&loaded.head,&loaded.objects,"master").borrow().snapshot.clone();
+        let root=loaded.objects.loadTree(&snapshot,".").unwrap();
+        let body=treeObject(&root).unwrap();
+        assert!(body.windows(17).any(|entry| entry==b"100755 hello.txt\0"));
+        assert!(body.windows(11).any(|entry| entry==b"40000 demo\0"));
+        assert_eq!(root.borrow().modes.get("./hello.txt"),Some(&MODE_EXECUTABLE));
+        assert_eq!(treeId(&root).unwrap(),snapshot);
+    }
+}
+
+//检出、快进和合并都按tree中的mode恢复可执行位，只改了mode的文件也算作改动
+#[cfg(unix)]
+#[test]
+fn mode_test() {
+    let _guard=sandbox("mode_test");
+    use gitc::gitUtils::index::{MODE_EXECUTABLE,MODE_FILE};
+    use std::os::unix::fs::PermissionsExt;
+    let executable=|path:&str| std::fs::metadata(path).unwrap().permissions().mode()&0o111!=0;
+    let chmod=|path:&str,mode:u32| std::fs::set_permissions(path,std::fs::Permissions::from_mode(mode)).unwrap();
+    let treeModes=|repo:&Repository,name:&str| {
+        let snapshot=tipCommit(&repo.head,&repo.objects,name).borrow().snapshot.clone();
+        let root=repo.objects.loadTree(&snapshot,".").unwrap();
+        let modes=root.borrow().modes.clone();
+        modes
+    };
+    let porcelain=|repo:&Repository| statusEntries(repo).unwrap().iter().map(|entry| entry.porcelain()).collect::<Vec<String>>();
+
+    let mut repo=Repository::open(None).unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"base","alex",false).unwrap();
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+
+    chmod("./hello.txt",0o755);
+    assert!(repo.head.index.isModified("./hello.txt").unwrap());
+    assert!(porcelain(&repo).contains(&String::from(" M ./hello.txt")));
+    std::fs::write("./run.sh","echo hi").unwrap();
+    chmod("./run.sh",0o755);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitAdd(&mut repo,"./run.sh".to_string(),false).unwrap();
+    assert!(porcelain(&repo).contains(&String::from("M  ./hello.txt")));
+    gitCommit(&mut repo,"executable","alex",false).unwrap();
+
+    //切回master时删除run.sh，hello.txt去掉执行权限
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert!(!Path::new("./run.sh").exists());
+    assert!(!executable("./hello.txt"));
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().mode,MODE_FILE);
+    assert!(statusEntries(&repo).unwrap().iter().all(|entry| entry.path!="./hello.txt"));
+
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    assert!(executable("./hello.txt") && executable("./run.sh"));
+    assert_eq!(repo.head.index.get("./run.sh").unwrap().mode,MODE_EXECUTABLE);
+    assert!(statusEntries(&repo).unwrap().iter().all(|entry| entry.path!="./hello.txt" && entry.path!="./run.sh"));
+    //再次提交时仍然是可执行文件
+    std::fs::write("./run.sh","echo hello").unwrap();
+    gitAdd(&mut repo,"./run.sh".to_string(),false).unwrap();
+    gitCommit(&mut repo,"again","alex",false).unwrap();
+    let modes=treeModes(&repo,"b1");
+    assert_eq!((modes.get("./hello.txt"),modes.get("./run.sh")),(Some(&MODE_EXECUTABLE),Some(&MODE_EXECUTABLE)));
+
+    //快进合并
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert_eq!(gitMerge(&mut repo,String::from("b1"),"merge","alex",MergeMode::FastForward,false).unwrap().outcome,MergeOutcome::FastForward);
+    assert!(executable("./hello.txt") && executable("./run.sh"));
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().mode,MODE_EXECUTABLE);
+
+    //三方合并：b2只改了mode，master只改了内容，合并结果两者都保留
+    gitBranch(&mut repo,String::from("b2"),false).unwrap();
+    gitCheckout(&mut repo,String::from("b2"),false,false).unwrap();
+    chmod("./hello.txt",0o644);
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"plain","alex",false).unwrap();
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    assert!(executable("./hello.txt"));
+    std::fs::write("./hello.txt","hello master").unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"content","alex",false).unwrap();
+    assert_eq!(gitMerge(&mut repo,String::from("b2"),"merge b2","alex",MergeMode::FastForward,false).unwrap().outcome,MergeOutcome::Merged);
+    assert!(!executable("./hello.txt") && executable("./run.sh"));
+    assert_eq!(std::fs::read("./hello.txt").unwrap(),b"hello master");
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().mode,MODE_FILE);
+    let modes=treeModes(&repo,"master");
+    assert_eq!((modes.get("./hello.txt"),modes.get("./run.sh")),(None,Some(&MODE_EXECUTABLE)));
+    assert!(statusEntries(&repo).unwrap().iter().all(|entry| entry.path!="./hello.txt" && entry.path!="./run.sh"));
+}
+
+#[test]
+fn rev_parse_test() {
+    let _guard=sandbox("rev_parse_test");
+    use gitc::gitMethods::gitRevParse::revParse;
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    let id=|repo:&Repository,rev:&str| revParse(repo,rev).unwrap();
+
+    //提交信息相同的commit互不覆盖，和路径相同的提交信息也不影响暂存区
+    gitAdd(&mut repo,"./hello.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"same","alex",false).unwrap();
+    let first=id(&repo,"HEAD");
+    gitAdd(&mut repo,"./hello1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"same","alex",false).unwrap();
+    let second=id(&repo,"master");
+    gitAdd(&mut repo,"./hello2.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"./hello.txt","alex",false).unwrap();
+    let third=id(&repo,"HEAD");
+    assert_eq!(repo.head.index.get("./hello.txt").unwrap().id,hashBlob(b"hello0"));
+    assert!(repo.head.currentBranch().unwrap().borrow().references.borrow().refermap.is_empty());
+    assert_ne!(first,second);
+    assert!(matches!(revParse(&repo,"same"),Err(GitcError::UnknownRevision(_))));
+
+    //~n沿第一个父commit后退，完整和缩写的SHA1都可以作为起点
+    assert_eq!(id(&repo,"HEAD~"),second);
+    assert_eq!(id(&repo,"HEAD~2"),first);
+    assert_eq!(id(&repo,"master~1~1"),first);
+    assert_eq!(id(&repo,&third[..7]),third);
+    assert_eq!(id(&repo,&format!("{}^",&second[..4])),first);
+    assert_eq!(id(&repo,&third.to_uppercase()),third);
+    assert_eq!(id(&repo,"HEAD^0"),third);
+    assert!(matches!(revParse(&repo,"HEAD~3"),Err(GitcError::UnknownRevision(_))));
+    assert!(matches!(revParse(&repo,"HEAD~x"),Err(GitcError::UnknownRevision(_))));
+    assert!(matches!(revParse(&repo,&third[..3]),Err(GitcError::UnknownRevision(_))));
+
+    //^2为合并进来的commit，tag和分支一样可以使用
+    gitBranch(&mut repo,String::from("b1"),false).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,false).unwrap();
+    std::fs::write("./demo/demo1.txt","b1").unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),false).unwrap();
+    gitCommit(&mut repo,"b1","bob",false).unwrap();
+    let b1=id(&repo,"b1");
+    gitCheckout(&mut repo,String::from("master"),false,false).unwrap();
+    gitMerge(&mut repo,String::from("b1"),"merge b1","alex",MergeMode::NoFastForward,false).unwrap();
+    assert_eq!(id(&repo,"HEAD^"),third);
+    assert_eq!(id(&repo,"HEAD^2"),b1);
+    assert_eq!(id(&repo,"HEAD^2~1"),third);
+    assert!(matches!(revParse(&repo,"HEAD^3"),Err(GitcError::UnknownRevision(_))));
+    std::fs::write("./.gitc/refs/tags/v1",format!("{}\n",second)).unwrap();
+    assert_eq!(id(&repo,"v1"),second);
+    assert_eq!(id(&repo,"v1~1"),first);
+    //tag名不能指向refs/tags之外的文件
+    std::fs::write("./outside",format!("{}\n",second)).unwrap();
+    let outside=std::env::current_dir().unwrap().join("outside");
+    assert!(matches!(revParse(&repo,outside.to_str().unwrap()),Err(GitcError::UnknownRevision(_))));
+    assert!(matches!(revParse(&repo,"../../../outside"),Err(GitcError::UnknownRevision(_))));
+}
+
+#[test]
+fn ancestry_test() {
+    let _guard=sandbox("ancestry_test");
+
+    let mut repo=Repository::new(HEAD::new(),Objects::new());
+    repo.head.currentBranchName=String::from("master");
+    repo.head.branch.insert(String::from("master"),Rc::new(RefCell::new(Branch::new(String::from("master")))));
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"c1","alex",true).unwrap();
+    gitBranch(&mut repo,String::from("b1"),true).unwrap();
+    gitAdd(&mut repo,"./hello1.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"c2","alex",true).unwrap();
+    gitAdd(&mut repo,"./hello2.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"c3","alex",true).unwrap();
+    gitCheckout(&mut repo,String::from("b1"),false,true).unwrap();
+    gitAdd(&mut repo,"./demo/demo1.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"b1","alex",true).unwrap();
+    gitCheckout(&mut repo,String::from("master"),false,true).unwrap();
+    gitMerge(&mut repo,String::from("b1"),"merge","alex",MergeMode::NoFastForward,true).unwrap();
+
+    //每个commit只记录直接的父commit
+    let tipId=repo.head.currentBranch().unwrap().borrow().commitpointer.clone().unwrap();
+    let tip=repo.objects.loadCommit(&tipId).unwrap();
+    let c3=repo.objects.loadCommit(&tip.borrow().parents[0]).unwrap();
+    let b1=repo.objects.loadCommit(&tip.borrow().mergeparents[0]).unwrap();
+    assert_eq!((tip.borrow().parents.len(),tip.borrow().mergeparents.len()),(1,1));
+    assert_eq!((c3.borrow().message.as_str(),b1.borrow().message.as_str()),("c3","b1"));
+    assert_eq!(c3.borrow().parents.len(),1);
+    assert!(c3.borrow().mergeparents.is_empty());
+    let c2=repo.objects.loadCommit(&c3.borrow().parents[0]).unwrap();
+    let c1=repo.objects.loadCommit(&c2.borrow().parents[0]).unwrap();
+    assert!(c1.borrow().parents.is_empty());
+    let names:Vec<String>=gitc::gitMethods::gitMergeBase::ancestors(&repo.objects,&tipId).unwrap().iter().map(|(_,commit)| commit.borrow().message.clone()).collect();
+    assert_eq!(names,vec!["merge","c3","b1","c2","c1"]);
+
+    //版本6之前在parents和mergeparents中记录整条祖先链，读取时只留下最后一个
+    std::fs::write("./.gitc/maps/head",bincode::serialize(&legacyHead(&repo.head,&repo.objects)).unwrap()).unwrap();
+    std::fs::write("./.gitc/maps/version","5").unwrap();
+    let oldSize=std::fs::metadata("./.gitc/maps/head").unwrap().len();
+    let (cache,migrated)=loadState(Path::new(".")).unwrap();
+    assert!(std::fs::metadata("./.gitc/maps/head").unwrap().len()<oldSize);
+    assert_eq!(migrated.currentBranch().unwrap().borrow().commitpointer.clone().unwrap(),tipId);
+    let tip=cache.loadCommit(&tipId).unwrap();
+    let parents:Vec<String>=tip.borrow().directParents().iter().map(|parent| cache.loadCommit(parent).unwrap().borrow().message.clone()).collect();
+    assert_eq!(parents,vec!["c3","b1"]);
+    let names:Vec<String>=gitc::gitMethods::gitMergeBase::ancestors(&cache,&tipId).unwrap().iter().map(|(_,commit)| commit.borrow().message.clone()).collect();
+    assert_eq!(names,vec!["merge","c3","b1","c2","c1"]);
+}
+
+#[test]
+fn shared_objects_test() {
+    let _guard=sandbox("shared_objects_test");
+    use gitc::gitMethods::gitMergeBase::ancestors;
+
+    let mut repo=Repository::open(None).unwrap();
+    gitAdd(&mut repo,"./demo".to_string(),true).unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"c0","alex",true).unwrap();
+    let firstId=repo.head.currentBranch().unwrap().borrow().commitpointer.clone().unwrap();
+    let size=std::fs::metadata("./.gitc/maps/head").unwrap().len();
+    for i in 1..6
+    {
+        std::fs::write("./hello.txt",format!("hello{}",i)).unwrap();
+        gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+        gitCommit(&mut repo,&format!("c{}",i),"alex",true).unwrap();
+    }
+
+    //分支只记录commit的SHA1，head的大小不随历史增长
+    assert_eq!(std::fs::metadata("./.gitc/maps/head").unwrap().len(),size);
+
+    //没有改动的文件夹在每个commit中都引用同一个tree
+    let (cache,loaded)=loadState(Path::new(".")).unwrap();
+    let demoTree=|commit:&Rc<RefCell<Commit>>| cache.loadTree(&commit.borrow().snapshot,".").unwrap().borrow().trees["./demo"].clone();
+    let tip=tipCommit(&loaded,&cache,"master");
+    assert_eq!(demoTree(&tip),demoTree(&cache.loadCommit(&firstId).unwrap()));
+    assert_eq!(cache.loadTree(&demoTree(&tip),"./demo").unwrap().borrow().blobs["./demo/demo1.txt"],hashBlob(b"demo1"));
+
+    //父commit按SHA1从objects中读出
+    let tipId=loaded.currentBranch().unwrap().borrow().commitpointer.clone().unwrap();
+    let history:Vec<String>=ancestors(&cache,&tipId).unwrap().into_iter().map(|(id,_)| id).collect();
+    assert_eq!(history.len(),6);
+    assert_eq!(history.last().unwrap(),&firstId);
+    assert_eq!(tip.borrow().parents,vec![history[1].clone()]);
+}
+
+#[test]
+fn signature_test() {
+    let _guard=sandbox("signature_test");
+    use gitc::gitUtils::signature::{Signature,parseOffset};
+
+    //时间的几种写法，不带时区时按UTC
+    assert_eq!(parseDate("1700000000 +0800"),Some((1700000000,480)));
+    assert_eq!(parseDate("@1700000000"),Some((1700000000,0)));
+    assert_eq!(parseDate("2023-11-15 06:13:20 +0800"),Some((1700000000,480)));
+    assert_eq!(parseDate("2023-11-14T22:13:20"),Some((1700000000,0)));
+    assert_eq!(parseDate("1969-12-31"),Some((-86400,0)));
+    assert_eq!(parseDate("yesterday"),None);
+    assert_eq!(parseDate("2023-13-01"),None);
+    assert_eq!(parseDate("1700000000 +0099"),None);
+    assert_eq!(parseOffset("+0959"),Some(599));
+    assert_eq!(parseOffset("+0060"),None);
+
+    let signature=Signature::decode("A U Thor <author@example.com> 1700000000 -0130").unwrap();
+    assert_eq!(signature,Signature::new(String::from("A U Thor"),String::from("author@example.com"),1700000000,-90));
+    assert_eq!(signature.encode(),"A U Thor <author@example.com> 1700000000 -0130");
+    assert_eq!(signature.date(),"Tue Nov 14 20:43:20 2023 -0130");
+    assert_eq!(Signature::parse("alex").ident(),"alex <>");
+    assert_eq!(Signature::parse("alex\n <a@b>>").ident(),"alex <a@b>");
+
+    //作者和提交者分别被环境变量覆盖，都写进commit对象，SHA1随之变化
+    let mut repo=Repository::open(None).unwrap();
+    gitAdd(&mut repo,"./hello.txt".to_string(),true).unwrap();
+    std::env::set_var("GITC_AUTHOR_DATE","2023-11-15 06:13:20 +0800");
+    std::env::set_var("GITC_COMMITTER_NAME","C O Mitter");
+    std::env::set_var("GITC_COMMITTER_EMAIL","committer@example.com");
+    std::env::set_var("GITC_COMMITTER_DATE","1700003600 +0000");
+    gitCommit(&mut repo,"first","alex <alex@example.com>",true).unwrap();
+    std::env::set_var("GITC_AUTHOR_NAME","bob");
+    std::env::set_var("GITC_COMMITTER_DATE","1700090000 +0000");
+    gitAdd(&mut repo,"./hello1.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"second","alex <alex@example.com>",true).unwrap();
+
+    let loaded=Repository::open(None).unwrap();
+    let tip=tipCommit(&loaded.head,&loaded.objects,"master");
+    let first=loaded.objects.loadCommit(&tip.borrow().parents[0]).unwrap();
+    assert_eq!(first.borrow().author,Signature::new(String::from("alex"),String::from("alex@example.com"),1700000000,480));
+    assert_eq!(first.borrow().committer,Signature::new(String::from("C O Mitter"),String::from("committer@example.com"),1700003600,0));
+    assert_eq!(tip.borrow().author.ident(),"bob <alex@example.com>");
+    let body=String::from_utf8(gitc::gitUtils::objectStore::commitObject(&first).unwrap()).unwrap();
+    assert!(body.contains("\nauthor alex <alex@example.com> 1700000000 +0800\ncommitter C O Mitter <committer@example.com> 1700003600 +0000\n"));
+
+    let full=logLines(&loaded,&LogOptions::default()).unwrap();
+    assert_eq!(full[1],"Author: bob <alex@example.com>");
+    assert_eq!(full[2],"Date:   Wed Nov 15 06:13:20 2023 +0800");
+    //--since和--until按提交时间过滤
+    let messages=|options:LogOptions|->Vec<String>
+    {
+        logLines(&loaded,&LogOptions{oneline:true,..options}).unwrap().iter().map(|line| line[8..].to_string()).collect()
+    };
+    assert_eq!(messages(LogOptions{since:Some(1700050000),..LogOptions::default()}),vec!["second"]);
+    assert_eq!(messages(LogOptions{until:Some(1700003600),..LogOptions::default()}),vec!["first"]);
+
+    //名字和邮箱中的换行和尖括号不能在commit对象中多出header
+    std::env::set_var("GITC_AUTHOR_NAME","eve\nparent 0000000000000000000000000000000000000000");
+    std::env::set_var("GITC_AUTHOR_EMAIL","<eve@example.com> 0 +0000\n");
+    gitAdd(&mut repo,"./hello2.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"third","alex",true).unwrap();
+    let loaded=Repository::open(None).unwrap();
+    let tip=tipCommit(&loaded.head,&loaded.objects,"master");
+    assert_eq!(tip.borrow().author.ident(),"eveparent 0000000000000000000000000000000000000000 <eve@example.com 0 +0000>");
+    assert_eq!(tip.borrow().directParents().len(),1);
+
+    std::env::set_var("GITC_AUTHOR_DATE","not a date");
+    assert!(matches!(gitCommit(&mut repo,"third","alex",false),Err(GitcError::InvalidDate(_))));
+}
+
+#[test]
+fn config_test() {
+    let _guard=sandbox("config_test");
+    use gitc::gitUtils::config::{readConfigFile,writeConfigFile,CONFIG_FILE};
+    use gitc::gitMethods::gitGc::DEFAULT_PRUNE;
+    let args=|line:&str| line.split(' ').map(String::from).collect::<Vec<String>>();
+
+    //INI格式：section和key不区分大小写，subsection区分，引号内的空白和注释符号原样保留
+    std::fs::write(CONFIG_FILE,"# repo config\n[User]\n\tName = alex ; comment\n[alias \"Co\"]\n\tx = \"a # b \"\n[core]\n\tbare\n").unwrap();
+    let entries=readConfigFile(Path::new(CONFIG_FILE)).unwrap();
+    assert_eq!(entries,vec![
+        (String::from("user.name"),String::from("alex")),
+        (String::from("alias.Co.x"),String::from("a # b ")),
+        (String::from("core.bare"),String::from("true")),
+    ]);
+
+    //set改写已有的键或加到section末尾，其余的行和注释不变；unset删除
+    assert!(writeConfigFile(Path::new(CONFIG_FILE),"user.name",Some("Alex Doe")).unwrap());
+    assert!(writeConfigFile(Path::new(CONFIG_FILE),"core.bare",None).unwrap());
+    assert!(!writeConfigFile(Path::new(CONFIG_FILE),"core.missing",None).unwrap());
+    assert!(matches!(writeConfigFile(Path::new(CONFIG_FILE),"nodot",Some("x")),Err(GitcError::InvalidConfig(_))));
+    run(&args("config set merge.conflictstyle diff3")).unwrap();
+    assert_eq!(std::fs::read_to_string(CONFIG_FILE).unwrap(),
+        "# repo config\n[User]\n\tname = Alex Doe\n[alias \"Co\"]\n\tx = \"a # b \"\n[core]\n[merge]\n\tconflictstyle = diff3\n");
+    run(&args("config unset merge.conflictstyle")).unwrap();
+    assert!(run(&args("config unset merge.conflictstyle")).is_err());
+    assert!(run(&args("config get merge.conflictstyle")).is_err());
+
+    //仓库配置覆盖用户级配置，环境变量又覆盖仓库配置
+    run(&args("config --global set user.name global")).unwrap();
+    run(&args("config --global set user.email global@example.com")).unwrap();
+    run(&args("config set core.compression 0")).unwrap();
+    let repo=Repository::open(None).unwrap();
+    assert_eq!(repo.head.config.identity().unwrap(),"Alex Doe <global@example.com>");
+    assert_eq!(repo.objects.compression,Some(0));
+    std::env::set_var("GITC_CONFIG_COUNT","2");
+    std::env::set_var("GITC_CONFIG_KEY_0","USER.NAME");
+    std::env::set_var("GITC_CONFIG_VALUE_0","env");
+    std::env::set_var("GITC_CONFIG_KEY_1","merge.conflictStyle");
+    std::env::set_var("GITC_CONFIG_VALUE_1","diff3");
+    let config=Config::load(Some(Path::new("."))).unwrap();
+    assert_eq!(config.get("user.name").unwrap(),"env");
+    assert_eq!(config.conflictStyle().unwrap(),ConflictStyle::Diff3);
+    assert_eq!(config.list().first().unwrap(),&(String::from("user.name"),String::from("global")));
+    std::env::remove_var("GITC_CONFIG_COUNT");
+
+    //作者默认取自user.name和user.email，压缩级别为0时对象仍然可以读出
+    run(&args("add hello.txt")).unwrap();
+    run(&args("commit -m first")).unwrap();
+    let repo=Repository::open(None).unwrap();
+    let tip=tipCommit(&repo.head,&repo.objects,"master");
+    assert_eq!(tip.borrow().author.ident(),"Alex Doe <global@example.com>");
+    assert_eq!(repo.objects.blobContents(&hashBlob(b"hello0")).unwrap(),b"hello0");
+
+    //别名展开后再执行，别名不能覆盖自带的命令，循环的别名报错
+    run(&args("config set alias.ci commit")).unwrap();
+    run(&args("config set alias.co checkout")).unwrap();
+    run(&args("config set alias.status log")).unwrap();
+    run(&args("config set alias.loop1 loop2")).unwrap();
+    run(&args("config set alias.loop2 loop1")).unwrap();
+    run(&args("branch b1")).unwrap();
+    run(&args("co b1")).unwrap();
+    run(&args("add hello1.txt")).unwrap();
+    run(&args("ci -m second")).unwrap();
+    run(&args("status")).unwrap();
+    assert!(run(&args("loop1")).is_err());
+    let repo=Repository::open(None).unwrap();
+    assert_eq!(repo.head.currentBranchName,"b1");
+    assert_eq!(tipCommit(&repo.head,&repo.objects,"b1").borrow().message,"second");
+
+    //gitMethods中各处的默认值
+    run(&args("config set merge.ff only")).unwrap();
+    run(&args("config set diff.algorithm patience")).unwrap();
+    run(&args("config set diff.context 1")).unwrap();
+    run(&args("config set gc.pruneExpire never")).unwrap();
+    let config=Config::load(Some(Path::new("."))).unwrap();
+    assert_eq!(MergeMode::fromConfig(&config).unwrap(),MergeMode::FastForwardOnly);
+    let options=DiffOptions::fromConfig(&config).unwrap();
+    assert_eq!((options.context,options.algorithm),(1,DiffAlgorithm::Patience));
+    assert_eq!(pruneExpire(&config).unwrap(),None);
+    run(&args("config set merge.ff false")).unwrap();
+    run(&args("config unset gc.pruneExpire")).unwrap();
+    let config=Config::load(Some(Path::new("."))).unwrap();
+    assert_eq!(MergeMode::fromConfig(&config).unwrap(),MergeMode::NoFastForward);
+    assert_eq!(pruneExpire(&config).unwrap(),Some(DEFAULT_PRUNE));
+
+    run(&args("config set core.compression 12")).unwrap();
+    assert!(matches!(loadState(Path::new(".")),Err(GitcError::InvalidConfig(_))));
+    run(&args("config unset core.compression")).unwrap();
+    assert_eq!(loadState(Path::new(".")).unwrap().0.compression,None);
+}
+
+#[test]
+fn repository_test() {
+    let _guard=sandbox("repository_test");
+    let root=std::env::current_dir().unwrap().canonicalize().unwrap();
+    let args=|line:&str| line.split(' ').map(String::from).collect::<Vec<String>>();
+
+    //在子目录中打开仓库时找到工作区的根目录并记住所在的子目录，当前目录不变
+    std::env::set_current_dir("./demo").unwrap();
+    let repo=Repository::open(None).unwrap();
+    assert_eq!(repo.root,root);
+    assert_eq!(repo.prefix,Path::new("demo"));
+    assert_eq!(std::env::current_dir().unwrap().canonicalize().unwrap(),root.join("demo"));
+
+    //命令行中的路径都相对打开时所在的目录
+    assert_eq!(repo.normalize("demo1.txt").unwrap(),"./demo/demo1.txt");
+    assert_eq!(repo.normalize("./a/../demo2.txt/").unwrap(),"./demo/demo2.txt");
+    assert_eq!(repo.normalize("..").unwrap(),".");
+    assert_eq!(repo.normalize("../hello.txt").unwrap(),"./hello.txt");
+    assert_eq!(repo.normalize(root.join("hello1.txt").to_str().unwrap()).unwrap(),"./hello1.txt");
+    assert!(matches!(repo.normalize("../../hello.txt"),Err(GitcError::OutsideRepository(_))));
+    assert!(matches!(repo.normalize("/"),Err(GitcError::OutsideRepository(_))));
+
+    run(&args("add demo1.txt ../hello.txt")).unwrap();
+    run(&args("commit -m first --author alex")).unwrap();
+    assert!(run(&args("add ../../hello.txt")).is_err());
+    assert_eq!(std::env::current_dir().unwrap().canonicalize().unwrap(),root.join("demo"));
+    let repo=Repository::open(None).unwrap();
+    let files=commitContents(&repo.objects,&tipCommit(&repo.head,&repo.objects,"master"));
+    assert_eq!(files.keys().collect::<Vec<_>>(),vec!["./demo/demo1.txt","./hello.txt"]);
+
+    //-C相当于在这个目录中运行，--git-dir直接指定.gitc，工作区为它所在的目录，两者都不切换当前目录
+    let outside=root.parent().unwrap();
+    std::env::set_current_dir(outside).unwrap();
+    let demo=root.join("demo");
+    run(&["-C".to_string(),demo.to_string_lossy().to_string(),"rm".to_string(),"--cached".to_string(),"demo1.txt".to_string()]).unwrap();
+    run(&[format!("--git-dir={}",root.join(".gitc").display()),"commit".to_string(),"-m".to_string(),"second".to_string()]).unwrap();
+    assert_eq!(std::env::current_dir().unwrap().canonicalize().unwrap(),outside);
+    let mut repo=Repository::open(Some(&root.join(".gitc"))).unwrap();
+    let files=commitContents(&repo.objects,&tipCommit(&repo.head,&repo.objects,"master"));
+    assert_eq!(files.keys().collect::<Vec<_>>(),vec!["./hello.txt"]);
+    assert!(demo.join("demo1.txt").is_file());
+
+    //在工作区之外直接调用操作，文件都按仓库的根目录读写
+    std::fs::write(root.join("hello2.txt"),"outside").unwrap();
+    gitAdd(&mut repo,"./hello2.txt".to_string(),true).unwrap();
+    gitCommit(&mut repo,"third","alex",true).unwrap();
+    std::fs::write(root.join("hello.txt"),"changed").unwrap();
+    let statuses:Vec<String>=statusEntries(&repo).unwrap().iter().map(|entry| entry.porcelain()).collect();
+    assert!(statuses.contains(&String::from(" M ./hello.txt")));
+    gitRm(&mut repo,"./hello2.txt".to_string(),false,false,false,true).unwrap();
+    assert!(!root.join("hello2.txt").exists());
+    assert_eq!(std::env::current_dir().unwrap().canonicalize().unwrap(),outside);
+    assert!(!outside.join(".gitc").exists());
+    let repo=Repository::open(Some(&root.join(".gitc"))).unwrap();
+    let files=commitContents(&repo.objects,&tipCommit(&repo.head,&repo.objects,"master"));
+    assert_eq!(files["./hello2.txt"],b"outside");
+    assert!(!repo.head.index.contains("./hello2.txt"));
+    assert!(matches!(Repository::open(Some(&demo)),Err(GitcError::NotARepository)));
+}